@@ -0,0 +1,56 @@
+//! Validates and optionally verifies a tax id from the command line.
+//!
+//! ```sh
+//! cargo run --example cli -- SE556703748501
+//! cargo run --example cli --features gb_vat -- GB591819014 --verify
+//! ```
+
+use std::process::ExitCode;
+
+use tax_ids::TaxId;
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let verify = args.iter().any(|arg| arg == "--verify");
+    let Some(value) = args.iter().find(|arg| !arg.starts_with("--")) else {
+        eprintln!("Usage: cli <VAT_NUMBER> [--verify]");
+        return ExitCode::FAILURE;
+    };
+
+    match TaxId::validate_syntax(value) {
+        Ok(()) => println!("Syntax:           valid"),
+        Err(e) => println!("Syntax:           {}", e),
+    }
+
+    let tax_id = match TaxId::new(value) {
+        Ok(tax_id) => tax_id,
+        Err(e) => {
+            eprintln!("ValidationError: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    println!("Value:            {}", tax_id.value());
+    println!("Type:             {} ({})", tax_id.human_name(), tax_id.tax_id_type());
+    println!("Country code:     {}", tax_id.country_code());
+    println!("Tax country code: {}", tax_id.tax_country_code());
+    println!("Local value:      {}", tax_id.local_value());
+    println!("Local validation: {:?}", tax_id.local_confidence());
+
+    if !verify {
+        return ExitCode::SUCCESS;
+    }
+
+    println!("Endpoint:         {}", tax_id.verification_endpoint());
+    match tax_id.verify() {
+        Ok(verification) => {
+            println!("Status:           {:?}", verification.status());
+            println!("Data:             {:#}", verification.data());
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("VerificationError: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,83 @@
+use std::collections::HashMap;
+use lazy_static::lazy_static;
+use regex::Regex;
+use crate::TaxIdType;
+use crate::offline::Offline;
+use crate::verification::Verifier;
+
+lazy_static! {
+    #[derive(Debug)]
+    pub static ref AE_TRN_PATTERN: HashMap<String, Regex> = {
+        let mut m = HashMap::new();
+        m.insert("AE".to_string(), Regex::new(r"^AE100[0-9]{12}$").unwrap());
+        m
+    };
+}
+
+#[derive(Debug)]
+pub struct AeTrn;
+
+impl TaxIdType for AeTrn {
+    fn name(&self) -> &'static str {
+        "ae_trn"
+    }
+
+    fn human_name(&self) -> &'static str {
+        "UAE Tax Registration Number (TRN)"
+    }
+
+    fn example(&self, tax_country_code: &str) -> Option<&'static str> {
+        (tax_country_code == "AE").then_some("AE100235617900003")
+    }
+
+    fn local_value_lengths(&self, tax_country_code: &str) -> Option<&'static [usize]> {
+        // Always starting with 100
+        (tax_country_code == "AE").then_some(&[15])
+    }
+
+    fn syntax_map(&self) -> &'static HashMap<String, Regex> {
+        &AE_TRN_PATTERN
+    }
+
+    fn country_code_from_tax_country(&self, tax_country_code: &str) -> String {
+        tax_country_code.to_string()
+    }
+
+    fn verifier(&self) -> &'static dyn Verifier {
+        &Offline
+    }
+
+    fn has_live_verifier(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "ae_trn")]
+    #[test]
+    fn test_ae_trns() {
+        let valid_vat_numbers = vec![
+            "AE100235617900003",
+            "AE100000000000000",
+        ];
+        let invalid_vat_numbers = vec![
+            "AE10023561790000",
+            "AE1002356179000031",
+            "AE200235617900003",
+            "AE010235617900003",
+            "AE10023561790000A",
+            "AE 100235617900003",
+        ];
+
+        for valid in valid_vat_numbers {
+            assert!(AeTrn::validate_syntax(&AeTrn, valid).is_ok());
+        }
+
+        for invalid in invalid_vat_numbers {
+            assert!(AeTrn::validate_syntax(&AeTrn, invalid).is_err());
+        }
+    }
+}
@@ -0,0 +1,110 @@
+use crate::errors::ValidationError;
+use crate::TaxId;
+
+/// Assembles a TaxId from a separately entered country and number, see `TaxId::builder`.
+///
+/// The country may be the ISO country code or the tax country code, i.e. GR and EL both give
+/// a Greek VAT number. The number may repeat the country prefix and contain separators.
+#[derive(Debug, Clone, Default)]
+pub struct TaxIdBuilder {
+    country: String,
+    number: String,
+}
+
+impl TaxIdBuilder {
+    /// The country code or tax country code. IE: SE, GR, EL or XI
+    pub fn country(mut self, country: &str) -> TaxIdBuilder {
+        self.country = country.trim().to_uppercase();
+        self
+    }
+
+    /// The number, with or without the country prefix. IE: 556703748501
+    pub fn number(mut self, number: &str) -> TaxIdBuilder {
+        self.number = number.trim().to_uppercase();
+        self
+    }
+
+    /// Assembles the value and delegates to `TaxId::new`, returning the same errors.
+    /// Without a country or number, `ValidationError::TooShort` is returned.
+    pub fn build(&self) -> Result<TaxId, ValidationError> {
+        TaxId::new(&self.value())
+    }
+
+    fn value(&self) -> String {
+        let mut tax_country_code = match self.country.as_str() {
+            "GR" => "EL",
+            country => country,
+        };
+        // Northern Ireland numbers are entered with GB as the country but keep their XI prefix
+        if tax_country_code == "GB" && self.number.starts_with("XI") {
+            tax_country_code = "XI";
+        }
+
+        let number = self.number.strip_prefix(tax_country_code).unwrap_or(&self.number);
+        if tax_country_code == "CH" && number.starts_with(|c: char| c.is_ascii_digit()) {
+            // The UID is often entered without its E, i.e. 116.281.710
+            let separator = if number.contains('.') { "-" } else { "" };
+            return format!("CHE{}{}", separator, number);
+        }
+
+        format!("{}{}", tax_country_code, number)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "eu_vat")]
+    #[test]
+    fn test_build_eu_vat() {
+        let tax_id = TaxId::builder().country("SE").number("556703748501").build().unwrap();
+        assert_eq!(tax_id.value(), "SE556703748501");
+
+        let tax_id = TaxId::builder().country("se").number("SE 5567 0374 8501").build().unwrap();
+        assert_eq!(tax_id.value(), "SE556703748501");
+
+        let tax_id = TaxId::builder().country("GR").number("094259216").build().unwrap();
+        assert_eq!(tax_id.tax_country_code(), "EL");
+        assert_eq!(tax_id.country_code(), "GR");
+
+        let tax_id = TaxId::builder().country("GB").number("XI591819014").build().unwrap();
+        assert_eq!(tax_id.tax_country_code(), "XI");
+    }
+
+    #[cfg(feature = "gb_vat")]
+    #[test]
+    fn test_build_gb_vat() {
+        let tax_id = TaxId::builder().country("GB").number("591 8190 14").build().unwrap();
+        assert_eq!(tax_id.value(), "GB591819014");
+        assert_eq!(tax_id.tax_id_type(), "gb_vat");
+    }
+
+    #[cfg(feature = "ch_vat")]
+    #[test]
+    fn test_build_ch_vat() {
+        for number in ["E-116.281.710", "CHE-116.281.710", "116.281.710"] {
+            let tax_id = TaxId::builder().country("CH").number(number).build().unwrap();
+            assert_eq!(tax_id.value(), "CHE-116.281.710", "{}", number);
+        }
+
+        let tax_id = TaxId::builder().country("CH").number("116281710").build().unwrap();
+        assert_eq!(tax_id.value(), "CHE116281710");
+    }
+
+    #[cfg(feature = "eu_vat")]
+    #[test]
+    fn test_build_mismatch() {
+        let result = TaxId::builder().country("SE").number("591819014").build();
+        assert_eq!(result.unwrap_err(), ValidationError::InvalidSyntax);
+
+        let result = TaxId::builder().country("XX").number("591819014").build();
+        assert_eq!(result.unwrap_err(), ValidationError::UnsupportedCountryCode("XX".to_string()));
+    }
+
+    #[test]
+    fn test_build_missing_parts() {
+        assert_eq!(TaxId::builder().build().unwrap_err(), ValidationError::TooShort);
+        assert_eq!(TaxId::builder().country("SE").build().unwrap_err(), ValidationError::TooShort);
+    }
+}
@@ -0,0 +1,167 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use chrono::prelude::*;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::errors::VerificationError;
+use crate::verification::Verification;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::verification::{VerificationConfig, VerificationStatus};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::TaxId;
+
+/// A store for verifications, used by `CachingVerifier` to avoid repeated government lookups.
+///
+/// Implement this to back the cache with i.e. Redis or a database table.
+pub trait VerificationCache {
+    /// Returns a verification stored under `key` that hasn't expired yet.
+    fn get(&self, key: &str) -> Option<Verification>;
+    /// Stores a verification under `key` for the given time to live.
+    fn put(&self, key: &str, verification: &Verification, ttl: Duration);
+}
+
+/// A `HashMap` backed `VerificationCache` living in the memory of the current process.
+#[derive(Debug, Default)]
+pub struct InMemoryCache {
+    entries: Mutex<HashMap<String, (DateTime<Local>, Verification)>>,
+}
+
+impl InMemoryCache {
+    pub fn new() -> InMemoryCache {
+        InMemoryCache::default()
+    }
+}
+
+impl VerificationCache for InMemoryCache {
+    fn get(&self, key: &str) -> Option<Verification> {
+        let entries = self.entries.lock().unwrap();
+        entries.get(key)
+            .filter(|(expires_at, _)| *expires_at > Local::now())
+            .map(|(_, verification)| verification.clone())
+    }
+
+    fn put(&self, key: &str, verification: &Verification, ttl: Duration) {
+        let Ok(ttl) = chrono::Duration::from_std(ttl) else { return };
+        let Some(expires_at) = Local::now().checked_add_signed(ttl) else { return };
+        let mut entries = self.entries.lock().unwrap();
+        entries.insert(key.to_string(), (expires_at, verification.clone()));
+    }
+}
+
+/// Wraps verification with a `VerificationCache`, keyed on the tax id value.
+///
+/// Only `Verified` and `Unverified` results are cached, an `Unavailable` result is
+/// always retried on the next call.
+#[cfg(not(target_arch = "wasm32"))]
+pub struct CachingVerifier<C: VerificationCache> {
+    cache: C,
+    ttl: Duration,
+    config: VerificationConfig,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl<C: VerificationCache> CachingVerifier<C> {
+    pub fn new(cache: C, ttl: Duration) -> CachingVerifier<C> {
+        CachingVerifier {
+            cache,
+            ttl,
+            config: VerificationConfig::default(),
+        }
+    }
+
+    /// Sets the `VerificationConfig` used for the verifications that miss the cache.
+    pub fn with_config(mut self, config: VerificationConfig) -> CachingVerifier<C> {
+        self.config = config;
+        self
+    }
+
+    pub fn cache(&self) -> &C { &self.cache }
+
+    /// Returns a cached verification for the tax id, or performs and caches a new one.
+    pub fn verify(&self, tax_id: &TaxId) -> Result<Verification, VerificationError> {
+        self.verify_with(tax_id, |tax_id| tax_id.verify_with_config(&self.config))
+    }
+
+    fn verify_with<F>(&self, tax_id: &TaxId, verify: F) -> Result<Verification, VerificationError>
+    where
+        F: FnOnce(&TaxId) -> Result<Verification, VerificationError>,
+    {
+        if let Some(verification) = self.cache.get(tax_id.value()) {
+            return Ok(verification);
+        }
+
+        let verification = verify(tax_id)?;
+        match verification.status() {
+            VerificationStatus::Verified | VerificationStatus::Unverified => {
+                self.cache.put(tax_id.value(), &verification, self.ttl);
+            }
+            VerificationStatus::Unavailable(_) => {}
+        }
+
+        Ok(verification)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use super::*;
+
+    #[test]
+    fn test_in_memory_cache_get_put() {
+        let cache = InMemoryCache::new();
+        let verification = Verification::new(VerificationStatus::Verified, json!({}));
+
+        assert_eq!(cache.get("SE123456789101"), None);
+        cache.put("SE123456789101", &verification, Duration::from_secs(60));
+        assert_eq!(cache.get("SE123456789101"), Some(verification));
+    }
+
+    #[test]
+    fn test_in_memory_cache_expired() {
+        let cache = InMemoryCache::new();
+        let verification = Verification::new(VerificationStatus::Verified, json!({}));
+
+        cache.put("SE123456789101", &verification, Duration::ZERO);
+        assert_eq!(cache.get("SE123456789101"), None);
+    }
+
+    #[cfg(feature = "eu_vat")]
+    #[test]
+    fn test_caching_verifier_hit_skips_request() {
+        let tax_id = TaxId::new("SE123456789101").unwrap();
+        let verifier = CachingVerifier::new(InMemoryCache::new(), Duration::from_secs(60));
+        let requests = std::cell::Cell::new(0);
+        let verify = |_: &TaxId| {
+            requests.set(requests.get() + 1);
+            Ok(Verification::new(VerificationStatus::Verified, json!({})))
+        };
+
+        let first = verifier.verify_with(&tax_id, verify).unwrap();
+        let second = verifier.verify_with(&tax_id, verify).unwrap();
+
+        assert_eq!(requests.get(), 1);
+        assert_eq!(first, second);
+    }
+
+    #[cfg(feature = "eu_vat")]
+    #[test]
+    fn test_caching_verifier_does_not_cache_unavailable() {
+        let tax_id = TaxId::new("SE123456789101").unwrap();
+        let verifier = CachingVerifier::new(InMemoryCache::new(), Duration::from_secs(60));
+        let requests = std::cell::Cell::new(0);
+        let verify = |_: &TaxId| {
+            requests.set(requests.get() + 1);
+            Ok(Verification::new(
+                VerificationStatus::Unavailable(crate::UnavailableReason::Timeout),
+                json!({})
+            ))
+        };
+
+        verifier.verify_with(&tax_id, verify).unwrap();
+        verifier.verify_with(&tax_id, verify).unwrap();
+
+        assert_eq!(requests.get(), 2);
+        assert_eq!(verifier.cache().get(tax_id.value()), None);
+    }
+}
@@ -1,9 +1,13 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use lazy_static::lazy_static;
-use reqwest::header::{HeaderMap, HeaderName, HeaderValue, ACCEPT, CONTENT_TYPE};
-use roxmltree;
+use regex::Regex;
+use reqwest::header::{HeaderMap, HeaderValue, ACCEPT, CONTENT_TYPE, USER_AGENT};
 use serde_json::json;
-use crate::verification::{Verifier, Verification, VerificationStatus::{*}, VerificationResponse, UnavailableReason::{*}};
+#[cfg(target_arch = "wasm32")]
+use crate::verification::ResponseFuture;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::verification::blocking_client;
+use crate::verification::{record_endpoint, user_agent, VerificationConfig, Verifier, Verification, VerificationStatus::{*}, VerificationResponse, UnavailableReason::{*}, Provider};
 use crate::errors::VerificationError;
 use crate::TaxId;
 
@@ -13,10 +17,12 @@ use crate::TaxId;
 // Bfs Accepted format: 'CHE123456789' or 'CHE-123.456.789' with optional space and
 // MWST/TVA/IVA extension: 'CHE123456789 MWST' or 'CHE-123.456.789 MWST'
 
-static URI: &'static str = "https://www.uid-wse-a.admin.ch/V5.0/PublicServices.svc";
+static URI: &str = "https://www.uid-wse-a.admin.ch/V5.0/PublicServices.svc";
 
-static ENVELOPE: &'static str = "
-    <soapenv:Envelope xmlns:soapenv=\"http://schemas.xmlsoap.org/soap/envelope/\" xmlns:uid=\"http://www.uid.admin.ch/xmlns/uid-wse\">
+static NAMESPACE: &str = "http://www.uid.admin.ch/xmlns/uid-wse";
+
+static ENVELOPE: &str = "
+    <soapenv:Envelope xmlns:soapenv=\"http://schemas.xmlsoap.org/soap/envelope/\" xmlns:uid=\"{namespace}\">
         <soapenv:Header/>
         <soapenv:Body>
             <uid:ValidateVatNumber>
@@ -26,19 +32,19 @@ static ENVELOPE: &'static str = "
     </soapenv:Envelope>
 ";
 
-const DATA_VALIDATION_FAILED: &str = "Data_validation_failed";
-const REQUEST_LIMIT_EXCEEDED: &str = "Request_limit_exceeded";
+const SOAP_ACTION: &str = "soapaction";
+pub(super) const DATA_VALIDATION_FAILED: &str = "Data_validation_failed";
+pub(super) const REQUEST_LIMIT_EXCEEDED: &str = "Request_limit_exceeded";
+pub(super) const INVALID_INPUT: &str = "invalid_input";
 
 lazy_static! {
+    static ref LIMIT_WINDOW: Regex = Regex::new(r"per (\d+) minute").unwrap();
+
     #[derive(Debug)]
     pub static ref HEADERS: HeaderMap = {
         let mut headers = HeaderMap::new();
         headers.insert(ACCEPT, HeaderValue::from_static("text/xml;charset=UTF-8"));
         headers.insert(CONTENT_TYPE, HeaderValue::from_static("text/xml;charset=UTF-8"));
-        headers.insert(
-            HeaderName::from_static("soapaction"),
-            HeaderValue::from_static("http://www.uid.admin.ch/xmlns/uid-wse/IPublicServices/ValidateVatNumber")
-        );
         headers
     };
 }
@@ -47,8 +53,95 @@ lazy_static! {
 pub struct Bfs;
 
 impl Bfs {
-    fn xml_to_hash(xml: &roxmltree::Document) -> HashMap<String, Option<String>> {
+    #[cfg(not(target_arch = "wasm32"))]
+    fn blocking_request(tax_id: &TaxId, config: &VerificationConfig) -> Result<reqwest::blocking::RequestBuilder, VerificationError> {
+        let uri = Bfs::uri(config);
+        record_endpoint(uri);
+        let request = blocking_client(config)?
+            .post(uri)
+            .header(USER_AGENT, user_agent(config))
+            .headers(HEADERS.clone())
+            .header(SOAP_ACTION, Bfs::soap_action(config))
+            .body(Bfs::envelope(tax_id, config));
+
+        Ok(request)
+    }
+
+    fn uri(config: &VerificationConfig) -> &str {
+        config.endpoints.bfs.as_deref().unwrap_or(URI)
+    }
+
+    fn namespace(config: &VerificationConfig) -> &str {
+        config.endpoints.bfs_namespace.as_deref().unwrap_or(NAMESPACE)
+    }
+
+    fn soap_action(config: &VerificationConfig) -> String {
+        format!("{}/IPublicServices/ValidateVatNumber", Bfs::namespace(config))
+    }
+
+    fn envelope(tax_id: &TaxId, config: &VerificationConfig) -> String {
+        ENVELOPE
+            .replace("{namespace}", Bfs::namespace(config))
+            .replace("{value}", tax_id.value())
+    }
+
+    /// The window in seconds of a rate limit detail like
+    /// "Maximum number of 20 requests per 1 minute(s) exceeded".
+    pub(super) fn limit_window(error_detail: &str) -> Option<u64> {
+        let captures = LIMIT_WINDOW.captures(error_detail)?;
+        let minutes: u64 = captures[1].parse().ok()?;
+        Some(minutes * 60)
+    }
+
+    fn parse(response: VerificationResponse, namespace: &str) -> Result<Verification, VerificationError> {
+        let doc = roxmltree::Document::parse(response.body())
+            .map_err(|e| VerificationError::xml("BFS", response.body(), e))?;
+        let hash = Bfs::xml_to_hash(&doc, namespace);
+        let fault_string = hash.get("faultstring")
+            .and_then(|x| x.as_deref());
+
+        let status = match fault_string {
+            Some(DATA_VALIDATION_FAILED) => {
+                // The number was refused as malformed, it wasn't looked up at all
+                let mut data = json!(hash);
+                data["reason"] = json!(INVALID_INPUT);
+                return Ok(Verification::new(Unverified, data));
+            },
+            Some(REQUEST_LIMIT_EXCEEDED) => {
+                let mut data = json!(hash);
+                if let Some(seconds) = hash.get("errorDetail").and_then(|x| x.as_deref()).and_then(Bfs::limit_window) {
+                    data["retryAfter"] = json!(seconds);
+                }
+                return Ok(Verification::new(Unavailable(RateLimit), data));
+            },
+            Some(_) => return Err(VerificationError::UnexpectedResponse(
+                format!("Unexpected faultstring: {}", fault_string.unwrap())
+            )),
+            None if doc.root_element().tag_name().name() == "Envelope"
+                && !doc.descendants().any(|node| node.tag_name().name() == "ValidateVatNumberResult") => {
+                // During maintenance BFS answers with an envelope holding neither a result nor a fault
+                return Ok(Verification::new(Unavailable(ServiceUnavailable), json!(hash)));
+            },
+            None => {
+                let result = hash.get("ValidateVatNumberResult").and_then(|x| x.as_deref());
+                match result {
+                    Some("true") => Verified,
+                    Some("false") => Unverified,
+                    None | Some(_) => return Err(VerificationError::UnexpectedResponse(
+                        "ValidateVatNumberResult should be 'true' or 'false'".to_string()
+                    )),
+                }
+            },
+        };
+
+        Ok(Verification::new(status, json!(hash)))
+    }
+
+    /// Flattens the response by local tag name. Elements of the uid-wse `namespace` are never
+    /// shadowed by a namesake from another namespace.
+    fn xml_to_hash(xml: &roxmltree::Document, namespace: &str) -> HashMap<String, Option<String>> {
         let mut hash = HashMap::new();
+        let mut in_namespace = HashSet::new();
         let tags_to_exclude = [
             "Body",
             "Envelope",
@@ -64,6 +157,12 @@ impl Bfs {
                 continue;
             }
 
+            if node.tag_name().namespace() == Some(namespace) {
+                in_namespace.insert(tag_name);
+            } else if in_namespace.contains(tag_name) {
+                continue;
+            }
+
             if let Some(text) = node.text() {
                 hash.insert(tag_name.to_string(), Some(text.to_string()));
             }
@@ -74,14 +173,9 @@ impl Bfs {
 }
 
 impl Verifier for Bfs {
-    fn make_request(&self, tax_id: &TaxId) -> Result<VerificationResponse, VerificationError> {
-        let client = reqwest::blocking::Client::new();
-        let body = ENVELOPE
-            .replace("{value}", tax_id.value());
-        let res = client
-            .post(URI)
-            .headers(HEADERS.clone())
-            .body(body)
+    #[cfg(not(target_arch = "wasm32"))]
+    fn make_request(&self, tax_id: &TaxId, config: &VerificationConfig) -> Result<VerificationResponse, VerificationError> {
+        let res = Bfs::blocking_request(tax_id, config)?
             .send()
             .map_err(VerificationError::HttpError)?;
 
@@ -93,31 +187,44 @@ impl Verifier for Bfs {
         )
     }
 
-    fn parse_response(&self, response: VerificationResponse) -> Result<Verification, VerificationError> {
-        let doc = roxmltree::Document::parse(response.body()).map_err(VerificationError::XmlParsingError)?;
-        let hash = Bfs::xml_to_hash(&doc);
-        let fault_string = hash.get("faultstring")
-            .and_then(|x| x.as_deref());
+    #[cfg(target_arch = "wasm32")]
+    fn make_request_async<'a>(&'a self, tax_id: &'a TaxId, config: &'a VerificationConfig) -> ResponseFuture<'a> {
+        Box::pin(async move {
+            let uri = Bfs::uri(config);
+            record_endpoint(uri);
+            let res = reqwest::Client::new()
+                .post(uri)
+                .header(USER_AGENT, user_agent(config))
+                .headers(HEADERS.clone())
+                .header(SOAP_ACTION, Bfs::soap_action(config))
+                .body(Bfs::envelope(tax_id, config))
+                .send()
+                .await
+                .map_err(VerificationError::HttpError)?;
+
+            Ok(
+                VerificationResponse::new(
+                    res.status().as_u16(),
+                    res.text().await.map_err(VerificationError::HttpError)?
+                )
+            )
+        })
+    }
 
-        let status = match fault_string {
-            Some(DATA_VALIDATION_FAILED) => Unverified,
-            Some(REQUEST_LIMIT_EXCEEDED) => Unavailable(RateLimit),
-            Some(_) => return Err(VerificationError::UnexpectedResponse(
-                format!("Unexpected faultstring: {}", fault_string.unwrap().to_string())
-            )),
-            None => {
-                let result = hash.get("ValidateVatNumberResult").and_then(|x| x.as_deref());
-                match result {
-                    Some("true") => Verified,
-                    Some("false") => Unverified,
-                    None | Some(_) => return Err(VerificationError::UnexpectedResponse(
-                        "ValidateVatNumberResult should be 'true' or 'false'".to_string()
-                    )),
-                }
-            },
-        };
+    fn provider(&self) -> Provider {
+        Provider::Bfs
+    }
 
-        Ok(Verification::new(status, json!(hash)))
+    fn endpoint(&self, _tax_id: &TaxId, config: &VerificationConfig) -> String {
+        Bfs::uri(config).to_string()
+    }
+
+    fn parse_response(&self, response: VerificationResponse) -> Result<Verification, VerificationError> {
+        Bfs::parse(response, NAMESPACE)
+    }
+
+    fn parse_response_with_config(&self, response: VerificationResponse, config: &VerificationConfig) -> Result<Verification, VerificationError> {
+        Bfs::parse(response, Bfs::namespace(config))
     }
 }
 
@@ -125,6 +232,52 @@ impl Verifier for Bfs {
 mod tests {
     use super::*;
 
+    #[cfg(feature = "ch_vat")]
+    #[test]
+    fn test_request_default_endpoint() {
+        let tax_id = TaxId::new("CHE123456789").unwrap();
+        let request = Bfs::blocking_request(&tax_id, &VerificationConfig::default()).unwrap().build().unwrap();
+
+        assert_eq!(request.url().as_str(), URI);
+        assert_eq!(request.headers()[SOAP_ACTION], "http://www.uid.admin.ch/xmlns/uid-wse/IPublicServices/ValidateVatNumber");
+        let body = std::str::from_utf8(request.body().unwrap().as_bytes().unwrap()).unwrap();
+        assert!(body.contains("xmlns:uid=\"http://www.uid.admin.ch/xmlns/uid-wse\""));
+        assert!(body.contains("<uid:vatNumber>CHE123456789</uid:vatNumber>"));
+    }
+
+    #[cfg(feature = "ch_vat")]
+    #[test]
+    fn test_request_custom_endpoint() {
+        let tax_id = TaxId::new("CHE123456789").unwrap();
+        let config = VerificationConfig {
+            endpoints: crate::Endpoints {
+                bfs: Some("https://www.uid-wse-a.admin.ch/V5.1/PublicServices.svc".to_string()),
+                bfs_namespace: Some("http://www.uid.admin.ch/xmlns/uid-wse/5.1".to_string()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let request = Bfs::blocking_request(&tax_id, &config).unwrap().build().unwrap();
+
+        assert_eq!(request.url().as_str(), "https://www.uid-wse-a.admin.ch/V5.1/PublicServices.svc");
+        assert_eq!(request.headers()[SOAP_ACTION], "http://www.uid.admin.ch/xmlns/uid-wse/5.1/IPublicServices/ValidateVatNumber");
+        let body = std::str::from_utf8(request.body().unwrap().as_bytes().unwrap()).unwrap();
+        assert!(body.contains("xmlns:uid=\"http://www.uid.admin.ch/xmlns/uid-wse/5.1\""));
+    }
+
+    #[cfg(feature = "ch_vat")]
+    #[test]
+    fn test_request_user_agent() {
+        let tax_id = TaxId::new("CHE123456789").unwrap();
+
+        let request = Bfs::blocking_request(&tax_id, &VerificationConfig::default()).unwrap().build().unwrap();
+        assert_eq!(request.headers()[USER_AGENT], concat!("tax-ids/", env!("CARGO_PKG_VERSION")));
+
+        let config = VerificationConfig { user_agent: Some("my-shop/1.0".to_string()), ..Default::default() };
+        let request = Bfs::blocking_request(&tax_id, &config).unwrap().build().unwrap();
+        assert_eq!(request.headers()[USER_AGENT], "my-shop/1.0");
+    }
+
     #[cfg(feature = "ch_vat")]
     #[test]
     fn test_bfs_xml_to_hash() {
@@ -138,9 +291,81 @@ mod tests {
             </s:Envelope>
         "#;
         let doc = roxmltree::Document::parse(xml).unwrap();
-        let hash = Bfs::xml_to_hash(&doc);
+        let hash = Bfs::xml_to_hash(&doc, NAMESPACE);
+
+        assert_eq!(hash.get("ValidateVatNumberResult"), Some(&Some("true".to_string())));
+    }
 
+    #[cfg(feature = "ch_vat")]
+    #[test]
+    fn test_parse_response_namesake_in_other_namespace() {
+        let xml = r#"
+            <s:Envelope xmlns:s="http://schemas.xmlsoap.org/soap/envelope/">
+                <s:Body>
+                    <ValidateVatNumberResponse xmlns="http://www.uid.admin.ch/xmlns/uid-wse">
+                        <ValidateVatNumberResult>true</ValidateVatNumberResult>
+                    </ValidateVatNumberResponse>
+                    <d:Audit xmlns:d="urn:example:decoy">
+                        <d:ValidateVatNumberResult>false</d:ValidateVatNumberResult>
+                    </d:Audit>
+                </s:Body>
+            </s:Envelope>
+        "#;
+        let doc = roxmltree::Document::parse(xml).unwrap();
+        let hash = Bfs::xml_to_hash(&doc, NAMESPACE);
         assert_eq!(hash.get("ValidateVatNumberResult"), Some(&Some("true".to_string())));
+
+        let verification = Bfs.parse_response(VerificationResponse::new(200, xml.to_string())).unwrap();
+        assert_eq!(verification.status(), &Verified);
+    }
+
+    #[cfg(feature = "ch_vat")]
+    #[test]
+    fn test_parse_response_with_custom_namespace() {
+        let xml = r#"
+            <s:Envelope xmlns:s="http://schemas.xmlsoap.org/soap/envelope/">
+                <s:Body>
+                    <ValidateVatNumberResponse xmlns="http://www.uid.admin.ch/xmlns/uid-wse/5.1">
+                        <ValidateVatNumberResult>false</ValidateVatNumberResult>
+                    </ValidateVatNumberResponse>
+                    <d:Audit xmlns:d="http://www.uid.admin.ch/xmlns/uid-wse">
+                        <d:ValidateVatNumberResult>true</d:ValidateVatNumberResult>
+                    </d:Audit>
+                </s:Body>
+            </s:Envelope>
+        "#;
+        let config = VerificationConfig {
+            endpoints: crate::Endpoints {
+                bfs_namespace: Some("http://www.uid.admin.ch/xmlns/uid-wse/5.1".to_string()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let verification = Bfs.parse_response_with_config(VerificationResponse::new(200, xml.to_string()), &config).unwrap();
+        assert_eq!(verification.status(), &Unverified);
+    }
+
+    #[cfg(feature = "ch_vat")]
+    #[test]
+    fn test_parse_response_non_xml() {
+        let response = VerificationResponse::new(
+            502,
+            "<html><body>Bad Gateway</body>".to_string()
+        );
+
+        let verifier = Bfs;
+        let error = verifier.parse_response(response).unwrap_err();
+
+        match &error {
+            VerificationError::XmlParsingError { provider, body_snippet, .. } => {
+                assert_eq!(*provider, "BFS");
+                assert_eq!(body_snippet, "<html><body>Bad Gateway</body>");
+            }
+            _ => panic!("Expected XmlParsingError"),
+        }
+        assert!(error.to_string().starts_with("XML parsing error from BFS: "));
+        assert!(std::error::Error::source(&error).is_some());
     }
 
     #[cfg(feature = "ch_vat")]
@@ -168,6 +393,28 @@ mod tests {
         }));
     }
 
+    #[cfg(feature = "ch_vat")]
+    #[test]
+    fn test_parse_verification() {
+        let body = r#"
+            <s:Envelope xmlns:s="http://schemas.xmlsoap.org/soap/envelope/">
+                <s:Body>
+                    <ValidateVatNumberResponse xmlns="http://www.uid.admin.ch/xmlns/uid-wse">
+                        <ValidateVatNumberResult>true</ValidateVatNumberResult>
+                    </ValidateVatNumberResponse>
+                </s:Body>
+            </s:Envelope>
+        "#;
+        let tax_id = TaxId::new("CHE-116.281.710").unwrap();
+
+        let verification = tax_id.parse_verification(200, body).unwrap();
+        assert_eq!(verification.status(), &Verified);
+        assert_eq!(verification.provider(), Provider::Bfs);
+
+        let verification = tax_id.parse_verification(200, &body.replace(">true<", ">false<")).unwrap();
+        assert_eq!(verification.status(), &Unverified);
+    }
+
     #[cfg(feature = "ch_vat")]
     #[test]
     fn test_parse_response_unverified() {
@@ -193,6 +440,34 @@ mod tests {
         }));
     }
 
+    #[cfg(feature = "ch_vat")]
+    #[test]
+    fn test_parse_response_invalid_input() {
+        let response = VerificationResponse::new(
+            500,
+            r#"
+                <s:Envelope xmlns:s="http://schemas.xmlsoap.org/soap/envelope/">
+                    <s:Body>
+                        <s:Fault>
+                            <faultcode>s:Client</faultcode>
+                            <faultstring xml:lang="de-CH">Data_validation_failed</faultstring>
+                        </s:Fault>
+                    </s:Body>
+                </s:Envelope>
+            "#.to_string()
+        );
+
+        let verifier = Bfs;
+        let verification = verifier.parse_response(response).unwrap();
+
+        assert_eq!(verification.status(), &Unverified);
+        assert_eq!(verification.data(), &json!({
+            "faultcode": "s:Client",
+            "faultstring": DATA_VALIDATION_FAILED,
+            "reason": "invalid_input"
+        }));
+    }
+
     #[cfg(feature = "ch_vat")]
     #[test]
     fn test_parse_response_unavailable() {
@@ -226,8 +501,10 @@ mod tests {
             "errorDetail": "Maximum number of 20 requests per 1 minute(s) exceeded",
             "operation": "ValidateVatNumber",
             "faultcode": "s:Client",
-            "faultstring": REQUEST_LIMIT_EXCEEDED
+            "faultstring": REQUEST_LIMIT_EXCEEDED,
+            "retryAfter": 60
         }));
+        assert_eq!(verification.retry_after(), Some(std::time::Duration::from_secs(60)));
     }
 
     #[cfg(feature = "ch_vat")]
@@ -311,4 +588,25 @@ mod tests {
             _ => panic!("Expected UnexpectedResponse error"),
         }
     }
+
+    #[cfg(feature = "ch_vat")]
+    #[test]
+    fn test_parse_response_missing_result() {
+        let response = VerificationResponse::new(
+            200,
+            r#"
+                <s:Envelope xmlns:s="http://schemas.xmlsoap.org/soap/envelope/">
+                    <s:Body xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xmlns:xsd="http://www.w3.org/2001/XMLSchema">
+                        <ValidateVatNumberResponse xmlns="http://www.uid.admin.ch/xmlns/uid-wse"/>
+                    </s:Body>
+                </s:Envelope>
+            "#.to_string()
+        );
+
+        let verification = Bfs.parse_response(response).unwrap();
+        assert_eq!(verification.status(), &Unavailable(ServiceUnavailable));
+
+        let response = VerificationResponse::new(200, "<maintenance/>".to_string());
+        assert!(matches!(Bfs.parse_response(response), Err(VerificationError::UnexpectedResponse(_))));
+    }
 }
@@ -0,0 +1,296 @@
+use reqwest::header::{ACCEPT, USER_AGENT};
+use reqwest::Url;
+use serde_json::{json, Value};
+#[cfg(target_arch = "wasm32")]
+use crate::verification::ResponseFuture;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::verification::blocking_client;
+use crate::verification::{record_endpoint, user_agent, VerificationConfig, Verifier, Verification, VerificationStatus::{*}, VerificationResponse, UnavailableReason::{*}, Provider};
+use crate::errors::VerificationError;
+use crate::TaxId;
+use super::bfs::{Bfs, DATA_VALIDATION_FAILED, INVALID_INPUT, REQUEST_LIMIT_EXCEEDED};
+
+// INFO(2026-10-15 agent):
+// EXPERIMENTAL: a REST/JSON counterpart of the SOAP binding in bfs.rs. The BFS interface
+// documentation linked in bfs.rs only describes the SOAP services, so the URL and the
+// response shape below are assumed rather than documented and may not match what BFS serves:
+// {"vatNumber": "CHE-116.281.710", "valid": true, "active": true} or, with the same fault
+// codes as SOAP, {"error": "Request_limit_exceeded", "errorDetail": "..."}.
+// Override the URL through `Endpoints::bfs_rest` if BFS serves it elsewhere.
+
+static URI: &str = "https://www.uid-wse-a.admin.ch/V5.0/PublicServices.svc/rest/ValidateVatNumber";
+
+#[derive(Debug)]
+pub struct BfsRest;
+
+impl BfsRest {
+    #[cfg(not(target_arch = "wasm32"))]
+    fn blocking_request(tax_id: &TaxId, config: &VerificationConfig) -> Result<reqwest::blocking::RequestBuilder, VerificationError> {
+        let uri = BfsRest::uri(tax_id, config)?;
+        record_endpoint(uri.as_str());
+        let request = blocking_client(config)?
+            .get(uri)
+            .header(USER_AGENT, user_agent(config))
+            .header(ACCEPT, "application/json");
+
+        Ok(request)
+    }
+
+    fn uri(tax_id: &TaxId, config: &VerificationConfig) -> Result<Url, VerificationError> {
+        let base_uri = config.endpoints.bfs_rest.as_deref().unwrap_or(URI);
+        Url::parse_with_params(base_uri, &[("vatNumber", tax_id.value())])
+            .map_err(|e| VerificationError::InvalidConfig(format!("Invalid BFS REST endpoint {}: {}", base_uri, e)))
+    }
+
+    /// Verified when valid and active.
+    fn qualify(hash: &serde_json::Map<String, Value>) -> Result<crate::VerificationStatus, VerificationError> {
+        let flag = |key: &str| hash.get(key).and_then(Value::as_bool).ok_or_else(|| {
+            VerificationError::UnexpectedResponse(format!("{} should be true or false", key))
+        });
+
+        match (flag("valid")?, flag("active")?) {
+            (true, true) => Ok(Verified),
+            _ => Ok(Unverified),
+        }
+    }
+}
+
+impl Verifier for BfsRest {
+    #[cfg(not(target_arch = "wasm32"))]
+    fn make_request(&self, tax_id: &TaxId, config: &VerificationConfig) -> Result<VerificationResponse, VerificationError> {
+        let res = BfsRest::blocking_request(tax_id, config)?
+            .send()
+            .map_err(VerificationError::HttpError)?;
+
+        Ok(
+            VerificationResponse::new(
+                res.status().as_u16(),
+                res.text().map_err(VerificationError::HttpError)?
+            )
+        )
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn make_request_async<'a>(&'a self, tax_id: &'a TaxId, config: &'a VerificationConfig) -> ResponseFuture<'a> {
+        Box::pin(async move {
+            let uri = BfsRest::uri(tax_id, config)?;
+            record_endpoint(uri.as_str());
+            let res = reqwest::Client::new()
+                .get(uri)
+                .header(USER_AGENT, user_agent(config))
+                .header(ACCEPT, "application/json")
+                .send()
+                .await
+                .map_err(VerificationError::HttpError)?;
+
+            Ok(
+                VerificationResponse::new(
+                    res.status().as_u16(),
+                    res.text().await.map_err(VerificationError::HttpError)?
+                )
+            )
+        })
+    }
+
+    fn provider(&self) -> Provider {
+        Provider::Bfs
+    }
+
+    /// A malformed `Endpoints::bfs_rest` override is returned as configured, with the tax id
+    /// appended like the other verifiers do, as `verify` fails before any request.
+    fn endpoint(&self, tax_id: &TaxId, config: &VerificationConfig) -> String {
+        BfsRest::uri(tax_id, config)
+            .map(String::from)
+            .unwrap_or_else(|_| format!(
+                "{}?vatNumber={}",
+                config.endpoints.bfs_rest.as_deref().unwrap_or(URI),
+                tax_id.value()
+            ))
+    }
+
+    fn parse_response(&self, response: VerificationResponse) -> Result<Verification, VerificationError> {
+        match response.status() {
+            // Faults come with a JSON body explaining them
+            200 | 400 | 429 => {},
+            500..=599 => return Ok(Verification::new(Unavailable(ServiceUnavailable), json!({}))),
+            status => return Err(VerificationError::UnexpectedStatusCode(status)),
+        }
+
+        let v: Value = serde_json::from_str(response.body())
+            .map_err(|e| VerificationError::json("BFS", response.body(), e))?;
+        let Some(hash) = v.as_object() else {
+            return Err(VerificationError::UnexpectedResponse(
+                "BFS REST response should be a JSON object".to_string()
+            ));
+        };
+
+        match hash.get("error").and_then(Value::as_str) {
+            Some(DATA_VALIDATION_FAILED) => {
+                // The number was refused as malformed, it wasn't looked up at all
+                let mut data = json!(hash);
+                data["reason"] = json!(INVALID_INPUT);
+                Ok(Verification::new(Unverified, data))
+            },
+            Some(REQUEST_LIMIT_EXCEEDED) => {
+                let mut data = json!(hash);
+                if let Some(seconds) = hash.get("errorDetail").and_then(Value::as_str).and_then(Bfs::limit_window) {
+                    data["retryAfter"] = json!(seconds);
+                }
+                Ok(Verification::new(Unavailable(RateLimit), data))
+            },
+            Some(error) => Err(VerificationError::UnexpectedResponse(
+                format!("Unexpected error: {}", error)
+            )),
+            None if response.status() == 200 => Ok(Verification::new(BfsRest::qualify(hash)?, json!(hash))),
+            None => Err(VerificationError::UnexpectedStatusCode(response.status())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "ch_vat")]
+    #[test]
+    fn test_request() {
+        let tax_id = TaxId::new("CHE-116.281.710").unwrap();
+        let request = BfsRest::blocking_request(&tax_id, &VerificationConfig::default()).unwrap().build().unwrap();
+
+        assert_eq!(request.url().as_str(), format!("{}?vatNumber=CHE-116.281.710", URI));
+        assert_eq!(request.headers()[ACCEPT], "application/json");
+        assert_eq!(request.headers()[USER_AGENT], concat!("tax-ids/", env!("CARGO_PKG_VERSION")));
+    }
+
+    #[cfg(feature = "ch_vat")]
+    #[test]
+    fn test_invalid_endpoint_override() {
+        let tax_id = TaxId::new("CHE-116.281.710").unwrap();
+        let config = VerificationConfig {
+            ch_via_rest: true,
+            endpoints: crate::Endpoints { bfs_rest: Some("not a url".to_string()), ..Default::default() },
+            ..Default::default()
+        };
+
+        match tax_id.verify_with_config(&config) {
+            Err(VerificationError::InvalidConfig(msg)) => assert!(msg.contains("not a url"), "{}", msg),
+            other => panic!("Expected InvalidConfig error, got {:?}", other),
+        }
+        assert_eq!(BfsRest.endpoint(&tax_id, &config), "not a url?vatNumber=CHE-116.281.710");
+    }
+
+    #[cfg(feature = "ch_vat")]
+    #[test]
+    fn test_parse_response_verified() {
+        let response = VerificationResponse::new(
+            200,
+            r#"{"vatNumber": "CHE-116.281.710", "valid": true, "active": true}"#.to_string()
+        );
+        let verification = BfsRest.parse_response(response).unwrap();
+
+        assert_eq!(verification.status(), &Verified);
+        assert_eq!(verification.data(), &json!({
+            "vatNumber": "CHE-116.281.710",
+            "valid": true,
+            "active": true
+        }));
+    }
+
+    #[cfg(feature = "ch_vat")]
+    #[test]
+    fn test_parse_verification() {
+        let tax_id = TaxId::new("CHE-116.281.710").unwrap();
+        let config = VerificationConfig { ch_via_rest: true, ..Default::default() };
+        let body = r#"{"vatNumber": "CHE-116.281.710", "valid": true, "active": true}"#;
+
+        let verification = tax_id.parse_verification_with_config(200, body, &config).unwrap();
+        assert_eq!(verification.status(), &Verified);
+        assert_eq!(verification.provider(), Provider::Bfs);
+    }
+
+    #[cfg(feature = "ch_vat")]
+    #[test]
+    fn test_parse_response_unverified() {
+        for body in [
+            r#"{"vatNumber": "CHE-116.281.710", "valid": false, "active": true}"#,
+            r#"{"vatNumber": "CHE-116.281.710", "valid": true, "active": false}"#,
+        ] {
+            let response = VerificationResponse::new(200, body.to_string());
+            let verification = BfsRest.parse_response(response).unwrap();
+
+            assert_eq!(verification.status(), &Unverified, "{}", body);
+        }
+    }
+
+    #[cfg(feature = "ch_vat")]
+    #[test]
+    fn test_parse_response_data_validation_failed() {
+        let response = VerificationResponse::new(
+            400,
+            r#"{"error": "Data_validation_failed", "errorDetail": "vatNumber is not valid"}"#.to_string()
+        );
+        let verification = BfsRest.parse_response(response).unwrap();
+
+        assert_eq!(verification.status(), &Unverified);
+        assert_eq!(verification.data()["reason"], "invalid_input");
+        assert_eq!(verification.data()["error"], "Data_validation_failed");
+    }
+
+    #[cfg(feature = "ch_vat")]
+    #[test]
+    fn test_parse_response_request_limit_exceeded() {
+        let response = VerificationResponse::new(
+            429,
+            r#"{"error": "Request_limit_exceeded", "errorDetail": "Maximum number of 20 requests per 1 minute(s) exceeded"}"#.to_string()
+        );
+        let verification = BfsRest.parse_response(response).unwrap();
+
+        assert_eq!(verification.status(), &Unavailable(RateLimit));
+        assert_eq!(verification.retry_after(), Some(std::time::Duration::from_secs(60)));
+    }
+
+    #[cfg(feature = "ch_vat")]
+    #[test]
+    fn test_parse_response_service_unavailable() {
+        for status in [500, 502, 503] {
+            let response = VerificationResponse::new(status, "<html>Service Unavailable</html>".to_string());
+            let verification = BfsRest.parse_response(response).unwrap();
+
+            assert_eq!(verification.status(), &Unavailable(ServiceUnavailable), "{}", status);
+        }
+    }
+
+    #[cfg(feature = "ch_vat")]
+    #[test]
+    fn test_parse_response_unexpected_status_code() {
+        let response = VerificationResponse::new(404, "<html>Not Found</html>".to_string());
+        assert!(matches!(BfsRest.parse_response(response), Err(VerificationError::UnexpectedStatusCode(404))));
+
+        let response = VerificationResponse::new(301, String::new());
+        assert!(matches!(BfsRest.parse_response(response), Err(VerificationError::UnexpectedStatusCode(301))));
+
+        // A fault status without a fault in the body
+        let response = VerificationResponse::new(400, r#"{"valid": false, "active": true}"#.to_string());
+        assert!(matches!(BfsRest.parse_response(response), Err(VerificationError::UnexpectedStatusCode(400))));
+    }
+
+    #[cfg(feature = "ch_vat")]
+    #[test]
+    fn test_parse_response_unexpected() {
+        let response = VerificationResponse::new(200, r#"{"error": "Unknown_error"}"#.to_string());
+        assert!(matches!(BfsRest.parse_response(response), Err(VerificationError::UnexpectedResponse(_))));
+
+        for body in [
+            r#"{"vatNumber": "CHE-116.281.710"}"#,
+            r#"{"vatNumber": "CHE-116.281.710", "valid": true}"#,
+            r#"{"vatNumber": "CHE-116.281.710", "valid": false}"#,
+            r#"{"vatNumber": "CHE-116.281.710", "valid": true, "active": "yes"}"#,
+        ] {
+            let response = VerificationResponse::new(200, body.to_string());
+            assert!(matches!(BfsRest.parse_response(response), Err(VerificationError::UnexpectedResponse(_))), "{}", body);
+        }
+
+        let response = VerificationResponse::new(200, "not json".to_string());
+        assert!(matches!(BfsRest.parse_response(response), Err(VerificationError::JsonParsingError { .. })));
+    }
+}
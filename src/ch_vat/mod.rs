@@ -1,4 +1,5 @@
 mod bfs;
+mod bfs_rest;
 
 use std::collections::HashMap;
 use lazy_static::lazy_static;
@@ -21,12 +22,32 @@ lazy_static! {
 #[derive(Debug)]
 pub struct ChVat;
 
+impl ChVat {
+    /// The verifier using the BFS REST/JSON interface instead of the default SOAP one.
+    pub fn rest_verifier(&self) -> &'static dyn Verifier {
+        &bfs_rest::BfsRest
+    }
+}
+
 impl TaxIdType for ChVat {
     fn name(&self) -> &'static str {
         "ch_vat"
     }
 
-    fn syntax_map(&self) -> &HashMap<String, Regex> {
+    fn human_name(&self) -> &'static str {
+        "Swiss VAT number (UID)"
+    }
+
+    fn example(&self, tax_country_code: &str) -> Option<&'static str> {
+        (tax_country_code == "CH").then_some("CHE-116.281.710")
+    }
+
+    fn local_value_lengths(&self, tax_country_code: &str) -> Option<&'static [usize]> {
+        // E123456789 or E-123.456.789, without the optional MWST/TVA/IVA suffix
+        (tax_country_code == "CH").then_some(&[10, 13])
+    }
+
+    fn syntax_map(&self) -> &'static HashMap<String, Regex> {
         &CH_VAT_PATTERN
     }
 
@@ -34,8 +55,8 @@ impl TaxIdType for ChVat {
         tax_country_code.to_string()
     }
 
-    fn verifier(&self) -> Box<dyn Verifier> {
-        Box::new(bfs::Bfs)
+    fn verifier(&self) -> &'static dyn Verifier {
+        &bfs::Bfs
     }
 }
 
@@ -0,0 +1,93 @@
+// Building blocks shared by the country specific check digit algorithms.
+// Digits are passed as their numeric values, i.e. `[1, 2, 3]` for "123".
+
+/// Extracts the numeric value of every decimal digit in `value`, skipping any other character.
+pub fn digits(value: &str) -> Vec<u8> {
+    value.chars()
+        .filter_map(|c| c.to_digit(10))
+        .map(|d| d as u8)
+        .collect()
+}
+
+/// Sum of each digit multiplied by its weight.
+/// Digits or weights beyond the length of the shorter slice are ignored.
+#[cfg(any(feature = "eu_vat", feature = "ua_edrpou"))]
+pub fn weighted_sum(digits: &[u8], weights: &[u8]) -> u32 {
+    digits.iter()
+        .zip(weights)
+        .map(|(d, w)| *d as u32 * *w as u32)
+        .sum()
+}
+
+/// Weighted sum of the digits modulo 11.
+/// Digits or weights beyond the length of the shorter slice are ignored.
+#[cfg(feature = "eu_vat")]
+pub fn mod11(digits: &[u8], weights: &[u8]) -> u8 {
+    (weighted_sum(digits, weights) % 11) as u8
+}
+
+/// ISO 7064 MOD 11,10 check digit over `digits`, a computed check of 10 becomes 0.
+#[cfg(any(feature = "eu_vat", feature = "rs_pib"))]
+pub fn mod11_10(digits: &[u8]) -> u8 {
+    let product = digits.iter().fold(10, |product, d| {
+        let sum = match (d + product) % 10 {
+            0 => 10,
+            sum => sum,
+        };
+        (2 * sum) % 11
+    });
+
+    (11 - product) % 10
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_digits() {
+        assert_eq!(digits("U1234"), vec![1, 2, 3, 4]);
+        assert_eq!(digits("1A2B"), vec![1, 2]);
+        assert_eq!(digits(""), Vec::<u8>::new());
+    }
+
+    #[cfg(any(feature = "eu_vat", feature = "ua_edrpou"))]
+    #[test]
+    fn test_weighted_sum() {
+        assert_eq!(weighted_sum(&[1, 2, 3], &[3, 2, 1]), 10);
+        assert_eq!(weighted_sum(&[9, 9, 9], &[10, 10, 10]), 270);
+        assert_eq!(weighted_sum(&[], &[]), 0);
+    }
+
+    #[cfg(any(feature = "eu_vat", feature = "ua_edrpou"))]
+    #[test]
+    fn test_weighted_sum_length_mismatch() {
+        assert_eq!(weighted_sum(&[1, 2, 3, 4], &[1, 1]), 3);
+        assert_eq!(weighted_sum(&[1, 2], &[1, 1, 1, 1]), 3);
+    }
+
+    #[cfg(feature = "eu_vat")]
+    #[test]
+    fn test_mod11() {
+        assert_eq!(mod11(&[2, 0, 7, 7, 4, 7, 4], &[7, 9, 10, 5, 8, 4, 2]), 0);
+        assert_eq!(mod11(&[1, 2, 3], &[3, 2, 1]), 10);
+        assert_eq!(mod11(&[1, 3, 5, 8, 5, 6, 2, 8], &[2, 7, 6, 5, 4, 3, 2, 1]), 0);
+        assert_eq!(mod11(&[0, 0, 0, 0], &[4, 3, 2, 1]), 0);
+        assert_eq!(mod11(&[1, 0, 0], &[10]), 10);
+    }
+
+    #[cfg(feature = "eu_vat")]
+    #[test]
+    fn test_mod11_length_mismatch() {
+        assert_eq!(mod11(&[5, 5, 5], &[1]), 5);
+        assert_eq!(mod11(&[5], &[1, 2, 3]), 5);
+    }
+
+    #[cfg(any(feature = "eu_vat", feature = "rs_pib"))]
+    #[test]
+    fn test_mod11_10() {
+        assert_eq!(mod11_10(&[1, 3, 6, 6, 9, 5, 9, 7]), 6);
+        assert_eq!(mod11_10(&[1, 0, 1, 1, 3, 4, 7, 0]), 2);
+        assert_eq!(mod11_10(&[]), 1);
+    }
+}
@@ -0,0 +1,152 @@
+use std::io::Read;
+
+use crate::{TaxId, ValidationError};
+
+/// How `validate_csv_with_options` reads its input.
+#[derive(Debug, Clone)]
+pub struct CsvOptions {
+    /// Skip the first record, i.e. a row of column names.
+    pub has_header: bool,
+    /// The byte separating the fields of a record.
+    pub delimiter: u8,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        CsvOptions { has_header: false, delimiter: b',' }
+    }
+}
+
+/// The outcome of validating the tax id of one CSV record.
+#[derive(Debug, PartialEq)]
+pub struct CsvValidationRow {
+    /// The 1-based record number, counting the header, i.e. to point the user at the row.
+    pub row: usize,
+    /// The field as found in the input, unquoted.
+    pub value: String,
+    /// The result of `TaxId::validate_syntax` on the value with surrounding whitespace trimmed.
+    /// A field that isn't UTF-8 fails with `ValidationError::InvalidEncoding`.
+    pub result: Result<(), ValidationError>,
+}
+
+/// Validates the syntax of the tax ids in the given `column` (0-based) of a comma separated
+/// input without header, i.e. a bulk import. See `validate_csv_with_options`.
+pub fn validate_csv<R: Read>(reader: R, column: usize) -> Vec<CsvValidationRow> {
+    validate_csv_with_options(reader, column, &CsvOptions::default())
+}
+
+/// Same as `validate_csv`, with the header and delimiter given by `options`.
+///
+/// Fields may be quoted with `"`, holding delimiters, line breaks and `""` as an escaped quote.
+/// Blank lines are skipped, a record without the column yields an empty value. Reading stops
+/// at the first I/O error, keeping the records read until then.
+pub fn validate_csv_with_options<R: Read>(mut reader: R, column: usize, options: &CsvOptions) -> Vec<CsvValidationRow> {
+    let mut input = Vec::new();
+    let _ = reader.read_to_end(&mut input);
+
+    records(&input, options.delimiter).into_iter()
+        .enumerate()
+        .skip(usize::from(options.has_header))
+        .filter(|(_, fields)| !(fields.len() == 1 && fields[0].is_empty()))
+        .map(|(index, mut fields)| {
+            let field = if column < fields.len() { fields.swap_remove(column) } else { Vec::new() };
+            let (value, result) = match String::from_utf8(field) {
+                Ok(value) => {
+                    let result = TaxId::validate_syntax(value.trim());
+                    (value, result)
+                },
+                Err(e) => (String::from_utf8_lossy(e.as_bytes()).to_string(), Err(ValidationError::InvalidEncoding)),
+            };
+
+            CsvValidationRow { row: index + 1, value, result }
+        })
+        .collect()
+}
+
+/// Splits the input into records of raw fields.
+fn records(input: &[u8], delimiter: u8) -> Vec<Vec<Vec<u8>>> {
+    let mut records = Vec::new();
+    let mut fields = Vec::new();
+    let mut field = Vec::new();
+    let mut in_quotes = false;
+    let mut bytes = input.iter().copied().peekable();
+
+    while let Some(byte) = bytes.next() {
+        match byte {
+            b'"' if in_quotes && bytes.peek() == Some(&b'"') => {
+                field.push(b'"');
+                bytes.next();
+            },
+            b'"' if in_quotes => in_quotes = false,
+            b'"' if field.is_empty() => in_quotes = true,
+            _ if in_quotes => field.push(byte),
+            b'\r' if bytes.peek() == Some(&b'\n') => {},
+            b'\n' => {
+                fields.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut fields));
+            },
+            _ if byte == delimiter => fields.push(std::mem::take(&mut field)),
+            _ => field.push(byte),
+        }
+    }
+
+    if !field.is_empty() || !fields.is_empty() {
+        fields.push(field);
+        records.push(fields);
+    }
+
+    records
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "csv")]
+    #[test]
+    fn test_records() {
+        let input = b"a,\"b,c\",\"d \"\"e\"\"\"\r\n\"f\ng\",h\n";
+        assert_eq!(records(input, b','), vec![
+            vec![b"a".to_vec(), b"b,c".to_vec(), b"d \"e\"".to_vec()],
+            vec![b"f\ng".to_vec(), b"h".to_vec()],
+        ]);
+
+        assert_eq!(records(b"a;b", b';'), vec![vec![b"a".to_vec(), b"b".to_vec()]]);
+        assert_eq!(records(b"", b','), Vec::<Vec<Vec<u8>>>::new());
+    }
+
+    #[cfg(feature = "eu_vat")]
+    #[test]
+    fn test_validate_csv() {
+        let input = "Acme AB,SE556703748501\nTypo AB,SE55670374850\n\n\"Quoted, AB\",\" DE136695976 \"\nNo number AB\n";
+
+        assert_eq!(validate_csv(input.as_bytes(), 1), vec![
+            CsvValidationRow { row: 1, value: "SE556703748501".to_string(), result: Ok(()) },
+            CsvValidationRow { row: 2, value: "SE55670374850".to_string(), result: Err(ValidationError::InvalidSyntax) },
+            CsvValidationRow { row: 4, value: " DE136695976 ".to_string(), result: Ok(()) },
+            CsvValidationRow { row: 5, value: "".to_string(), result: Err(ValidationError::TooShort) },
+        ]);
+    }
+
+    #[cfg(feature = "eu_vat")]
+    #[test]
+    fn test_validate_csv_with_options() {
+        let input = "vat_number;name\nSE556703748501;Acme AB\nXX123456789;Unknown\n";
+        let options = CsvOptions { has_header: true, delimiter: b';' };
+
+        assert_eq!(validate_csv_with_options(input.as_bytes(), 0, &options), vec![
+            CsvValidationRow { row: 2, value: "SE556703748501".to_string(), result: Ok(()) },
+            CsvValidationRow { row: 3, value: "XX123456789".to_string(), result: Err(ValidationError::UnsupportedCountryCode("XX".to_string())) },
+        ]);
+    }
+
+    #[cfg(feature = "csv")]
+    #[test]
+    fn test_validate_csv_invalid_encoding() {
+        let input = b"SE\xff\n";
+
+        let rows = validate_csv(&input[..], 0);
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].result, Err(ValidationError::InvalidEncoding));
+    }
+}
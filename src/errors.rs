@@ -10,6 +10,30 @@ pub enum ValidationError {
     #[error("Invalid syntax")]
     /// The syntax of the tax id is invalid for the given country
     InvalidSyntax,
+
+    #[error("Invalid checksum")]
+    /// The tax id has a valid syntax but its check digit(s) do not add up
+    InvalidChecksum,
+
+    #[error("Invalid encoding, expected UTF-8")]
+    /// The tax id was provided as bytes that are not valid UTF-8
+    InvalidEncoding,
+
+    #[error("No checksum algorithm available for country code {0}")]
+    /// Strict validation was requested but the country's check digits can't be validated
+    ChecksumNotAvailable(String),
+
+    #[error("Too short, expected a country code followed by a number")]
+    /// The value is empty or holds no more than a country code
+    TooShort,
+
+    #[error("Suspected dummy tax id")]
+    /// The local value is a placeholder like repeated or consecutive digits, see `TaxId::new_rejecting_dummies`
+    SuspectedDummy,
+
+    #[error("Country code {0} is not allowed")]
+    /// The tax id is valid but its country is not in the allowlist given to `TaxId::new_restricted`
+    CountryNotAllowed(String),
 }
 
 #[derive(thiserror::Error)]
@@ -17,8 +41,13 @@ pub enum VerificationError {
     #[error("HTTP client error: {0}")]
     HttpError(#[from] reqwest::Error),
 
-    #[error("JSON parsing error: {0}")]
-    JsonParsingError(#[source] serde_json::Error),
+    #[error("JSON parsing error from {provider}: {source} (body: {body_snippet})")]
+    JsonParsingError {
+        provider: &'static str,
+        body_snippet: String,
+        #[source]
+        source: serde_json::Error,
+    },
 
     #[error("Unexpected response: {0}")]
     UnexpectedResponse(String),
@@ -26,9 +55,60 @@ pub enum VerificationError {
     #[error("Unexpected status code: {0}")]
     UnexpectedStatusCode(u16),
 
+    #[error("Country code {0} is not supported")]
+    /// No verifier is available for the country code, see `service_health`
+    UnsupportedCountryCode(String),
+
+    #[error("Invalid configuration: {0}")]
+    /// The `VerificationConfig` can't be used, i.e. a malformed endpoint override. No request was sent
+    InvalidConfig(String),
+
     #[cfg(any(feature = "eu_vat", feature = "ch_vat"))]
-    #[error("XML parsing error: {0}")]
-    XmlParsingError(#[from] roxmltree::Error),
+    #[error("XML parsing error from {provider}: {source} (body: {body_snippet})")]
+    XmlParsingError {
+        provider: &'static str,
+        body_snippet: String,
+        #[source]
+        source: roxmltree::Error,
+    },
+}
+
+/// How much of an unparsable response body is kept on a parsing error.
+const BODY_SNIPPET_LENGTH: usize = 100;
+
+fn body_snippet(body: &str) -> String {
+    body.trim().chars().take(BODY_SNIPPET_LENGTH).collect()
+}
+
+impl VerificationError {
+    #[allow(dead_code)]
+    pub(crate) fn json(provider: &'static str, body: &str, source: serde_json::Error) -> VerificationError {
+        VerificationError::JsonParsingError { provider, body_snippet: body_snippet(body), source }
+    }
+
+    #[cfg(any(feature = "eu_vat", feature = "ch_vat"))]
+    pub(crate) fn xml(provider: &'static str, body: &str, source: roxmltree::Error) -> VerificationError {
+        VerificationError::XmlParsingError { provider, body_snippet: body_snippet(body), source }
+    }
+
+    /// Whether retrying the same verification later may succeed: timeouts, connection
+    /// failures and server side or rate limit status codes. Responses that couldn't be
+    /// parsed or weren't expected are not retryable.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            VerificationError::HttpError(e) => {
+                #[cfg(not(target_arch = "wasm32"))]
+                { e.is_timeout() || e.is_connect() }
+                #[cfg(target_arch = "wasm32")]
+                { e.is_timeout() }
+            },
+            VerificationError::UnexpectedStatusCode(status) => *status == 429 || (500..600).contains(status),
+            VerificationError::JsonParsingError { .. } | VerificationError::UnexpectedResponse(_) => false,
+            VerificationError::UnsupportedCountryCode(_) | VerificationError::InvalidConfig(_) => false,
+            #[cfg(any(feature = "eu_vat", feature = "ch_vat"))]
+            VerificationError::XmlParsingError { .. } => false,
+        }
+    }
 }
 
 impl Debug for VerificationError {
@@ -41,3 +121,70 @@ impl Debug for VerificationError {
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_is_retryable_http_timeout() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let uri = format!("http://{}", listener.local_addr().unwrap());
+        let error = reqwest::blocking::Client::builder()
+            .timeout(Duration::from_millis(50))
+            .build()
+            .unwrap()
+            .get(uri)
+            .send()
+            .unwrap_err();
+
+        assert!(error.is_timeout());
+        assert!(VerificationError::HttpError(error).is_retryable());
+    }
+
+    #[test]
+    fn test_is_retryable_http_connect() {
+        // Bind and drop to get a port nothing listens on
+        let addr = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+        let error = reqwest::blocking::get(format!("http://{}", addr)).unwrap_err();
+
+        assert!(error.is_connect());
+        assert!(VerificationError::HttpError(error).is_retryable());
+    }
+
+    #[test]
+    fn test_is_retryable_http_builder() {
+        let error = reqwest::blocking::get("not a url").unwrap_err();
+        assert!(!VerificationError::HttpError(error).is_retryable());
+    }
+
+    #[test]
+    fn test_is_retryable_status_codes() {
+        assert!(VerificationError::UnexpectedStatusCode(429).is_retryable());
+        assert!(VerificationError::UnexpectedStatusCode(502).is_retryable());
+        assert!(VerificationError::UnexpectedStatusCode(503).is_retryable());
+        assert!(!VerificationError::UnexpectedStatusCode(400).is_retryable());
+        assert!(!VerificationError::UnexpectedStatusCode(403).is_retryable());
+    }
+
+    #[test]
+    fn test_is_retryable_parsing() {
+        let source = serde_json::from_str::<serde_json::Value>("<html>").unwrap_err();
+        assert!(!VerificationError::json("BrReg", "<html>", source).is_retryable());
+        assert!(!VerificationError::UnexpectedResponse("Missing valid field".to_string()).is_retryable());
+    }
+
+    #[test]
+    fn test_is_retryable_unsupported_country_code() {
+        assert!(!VerificationError::UnsupportedCountryCode("XX".to_string()).is_retryable());
+        assert!(!VerificationError::InvalidConfig("Invalid BFS REST endpoint".to_string()).is_retryable());
+    }
+
+    #[cfg(any(feature = "eu_vat", feature = "ch_vat"))]
+    #[test]
+    fn test_is_retryable_xml_parsing() {
+        let source = roxmltree::Document::parse("<env:Envelope>").unwrap_err();
+        assert!(!VerificationError::xml("VIES", "<env:Envelope>", source).is_retryable());
+    }
+}
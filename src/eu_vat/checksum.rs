@@ -0,0 +1,481 @@
+// Check digit algorithms per member state. Each function receives the local value
+// (the VAT number without its tax country code) of an already syntax-validated number.
+
+use crate::checksum::{digits, mod11, mod11_10, weighted_sum};
+
+/// Austria: Luhn-style sum over the seven digits following the `U` prefix, the check digit
+/// being `96 - sum` modulo 10.
+pub fn at(local_value: &str) -> bool {
+    let digits = digits(local_value.trim_start_matches('U'));
+    let sum: u32 = digits[..7].iter().enumerate()
+        .map(|(i, d)| {
+            let product = if i % 2 == 0 { *d as u32 } else { *d as u32 * 2 };
+            product / 10 + product % 10
+        })
+        .sum();
+
+    (96 - sum) % 10 == digits[7] as u32
+}
+
+/// Bulgaria: mod 11 over the first eight digits of the 9-digit legal entity form with weights 1 to 8.
+/// When that yields 10, a second pass with weights 3 to 10 is used instead.
+///
+/// The 10 digit forms (individuals and foreigners) are only syntax-checked for now.
+pub fn bg(local_value: &str) -> bool {
+    const WEIGHTS: [u8; 8] = [1, 2, 3, 4, 5, 6, 7, 8];
+    const FALLBACK_WEIGHTS: [u8; 8] = [3, 4, 5, 6, 7, 8, 9, 10];
+    let digits = digits(local_value);
+    if digits.len() != 9 {
+        return true;
+    }
+    let remainder = match mod11(&digits, &WEIGHTS) {
+        10 => mod11(&digits, &FALLBACK_WEIGHTS),
+        remainder => remainder,
+    };
+
+    remainder % 10 == digits[8]
+}
+
+/// Cyprus: digits in even positions are translated through a fixed table, summed with the
+/// remaining digits and mapped modulo 26 onto the check letter. The prefix 12 is reserved.
+pub fn cy(local_value: &str) -> bool {
+    const TRANSLATION: [u32; 10] = [1, 0, 5, 7, 9, 13, 15, 17, 19, 21];
+    if local_value.starts_with("12") {
+        return false;
+    }
+    let sum: u32 = digits(&local_value[..8]).iter().enumerate()
+        .map(|(i, d)| if i % 2 == 0 { TRANSLATION[*d as usize] } else { *d as u32 })
+        .sum();
+
+    local_value[8..].starts_with((b'A' + (sum % 26) as u8) as char)
+}
+
+/// Czech Republic: mod 11 over the first seven digits of the 8-digit legal entity form.
+///
+/// The 9 and 10 digit forms (individuals) use birth-number based rules and are only
+/// syntax-checked for now.
+pub fn cz(local_value: &str) -> bool {
+    const WEIGHTS: [u8; 7] = [8, 7, 6, 5, 4, 3, 2];
+    let digits = digits(local_value);
+    if digits.len() != 8 {
+        return true;
+    }
+    let check = match (11 - mod11(&digits, &WEIGHTS)) % 11 {
+        0 => 1,
+        check => check % 10,
+    };
+
+    check == digits[7]
+}
+
+/// Germany: ISO 7064 MOD 11,10 over the first eight digits, a computed check of 10 becomes 0.
+pub fn de(local_value: &str) -> bool {
+    let digits = digits(local_value);
+
+    mod11_10(&digits[..8]) == digits[8]
+}
+
+/// Denmark: the weighted sum of all eight digits must be divisible by 11.
+pub fn dk(local_value: &str) -> bool {
+    const WEIGHTS: [u8; 8] = [2, 7, 6, 5, 4, 3, 2, 1];
+
+    mod11(&digits(local_value), &WEIGHTS) == 0
+}
+
+/// Estonia: the first eight digits are weighted with 3, 7, 1; the check digit tops the sum up
+/// to the next multiple of ten.
+pub fn ee(local_value: &str) -> bool {
+    const WEIGHTS: [u8; 8] = [3, 7, 1, 3, 7, 1, 3, 7];
+    let digits = digits(local_value);
+    let sum = weighted_sum(&digits, &WEIGHTS);
+
+    (10 - sum % 10) % 10 == digits[8] as u32
+}
+
+/// Greece: the first eight digits weighted with the powers of two from 256 down to 2,
+/// the sum modulo 11 and then modulo 10 being the check digit.
+pub fn el(local_value: &str) -> bool {
+    let digits = digits(local_value);
+    let sum: u32 = digits[..8].iter().enumerate()
+        .map(|(i, d)| *d as u32 * (1 << (8 - i)))
+        .sum();
+
+    sum % 11 % 10 == digits[8] as u32
+}
+
+/// Finland: mod 11 over the first seven digits. A remainder of 1 has no valid check digit.
+pub fn fi(local_value: &str) -> bool {
+    const WEIGHTS: [u8; 7] = [7, 9, 10, 5, 8, 4, 2];
+    let digits = digits(local_value);
+    let check = match mod11(&digits, &WEIGHTS) {
+        0 => 0,
+        1 => return false,
+        remainder => 11 - remainder,
+    };
+
+    check == digits[7]
+}
+
+/// Hungary: the first seven digits are weighted with 9, 7, 3, 1; the check digit tops the sum
+/// up to the next multiple of ten.
+pub fn hu(local_value: &str) -> bool {
+    const WEIGHTS: [u8; 7] = [9, 7, 3, 1, 9, 7, 3];
+    let digits = digits(local_value);
+    let sum = weighted_sum(&digits, &WEIGHTS);
+
+    (10 - sum % 10) % 10 == digits[7] as u32
+}
+
+/// Ireland: mod 23 over the seven digits (and the optional second letter), mapped to a check letter.
+///
+/// Old style numbers (digit, letter, five digits, check letter) are first rearranged into the
+/// current seven digit form.
+pub fn ie(local_value: &str) -> bool {
+    const ALPHABET: &str = "WABCDEFGHIJKLMNOPQRSTUV";
+    const WEIGHTS: [u8; 7] = [8, 7, 6, 5, 4, 3, 2];
+    let chars: Vec<char> = local_value.chars().collect();
+    let (base, extension) = if chars[1].is_ascii_alphabetic() {
+        (format!("0{}{}", &local_value[2..7], &local_value[..1]), None)
+    } else {
+        (local_value[..7].to_string(), chars.get(8))
+    };
+    let extension = match extension {
+        None => 0,
+        Some(letter) => match ALPHABET.find(*letter) {
+            Some(index) => index as u32,
+            None => return false,
+        },
+    };
+    let sum = weighted_sum(&digits(&base), &WEIGHTS) + 9 * extension;
+
+    ALPHABET.chars().nth((sum % 23) as usize) == Some(chars[7])
+}
+
+/// Lithuania: mod 11 over all but the last digit with weights cycling 1 to 9. When that yields 10,
+/// a second pass with weights cycling from 3 is used instead. Applies to both 9 and 12 digit numbers.
+pub fn lt(local_value: &str) -> bool {
+    let digits = digits(local_value);
+    let (base, check) = digits.split_at(digits.len() - 1);
+    let weights = |offset: usize| -> Vec<u8> {
+        (0..base.len()).map(|i| 1 + ((i + offset) % 9) as u8).collect()
+    };
+    let remainder = match mod11(base, &weights(0)) {
+        10 => mod11(base, &weights(2)),
+        remainder => remainder,
+    };
+
+    remainder % 10 == check[0]
+}
+
+/// Luxembourg: the last two digits equal the first six modulo 89.
+pub fn lu(local_value: &str) -> bool {
+    let (base, check) = local_value.split_at(6);
+    match (base.parse::<u32>(), check.parse::<u32>()) {
+        (Ok(base), Ok(check)) => base % 89 == check,
+        _ => false,
+    }
+}
+
+/// Latvia: legal entities (first digit above 3) have a mod 11 check over all eleven digits,
+/// which must leave a remainder of 3.
+///
+/// Natural person numbers encode a birth date and are only syntax-checked.
+pub fn lv(local_value: &str) -> bool {
+    const WEIGHTS: [u8; 11] = [9, 1, 4, 8, 3, 10, 2, 5, 7, 6, 1];
+    let digits = digits(local_value);
+    if digits[0] <= 3 {
+        return true;
+    }
+
+    mod11(&digits, &WEIGHTS) == 3
+}
+
+/// Malta: the last two digits equal 37 minus the weighted sum of the first six modulo 37.
+pub fn mt(local_value: &str) -> bool {
+    const WEIGHTS: [u8; 6] = [3, 4, 6, 7, 8, 9];
+    let digits = digits(local_value);
+    let check = 37 - weighted_sum(&digits, &WEIGHTS) % 37;
+
+    check == digits[6] as u32 * 10 + digits[7] as u32
+}
+
+/// Poland: mod 11 over the first nine digits. A remainder of 10 has no valid check digit.
+pub fn pl(local_value: &str) -> bool {
+    const WEIGHTS: [u8; 9] = [6, 5, 7, 2, 3, 4, 5, 6, 7];
+    let digits = digits(local_value);
+    let remainder = mod11(&digits, &WEIGHTS);
+
+    remainder != 10 && remainder == digits[9]
+}
+
+/// Portugal: mod 11 over the first eight digits with weights 9 down to 2.
+/// A computed check of 10 or 11 is written as 0.
+pub fn pt(local_value: &str) -> bool {
+    const WEIGHTS: [u8; 8] = [9, 8, 7, 6, 5, 4, 3, 2];
+    let digits = digits(local_value);
+    let check = 11 - mod11(&digits, &WEIGHTS);
+
+    check % 11 % 10 == digits[8]
+}
+
+/// Romania: the base digits are weighted with the control key 753217532, right-aligned.
+pub fn ro(local_value: &str) -> bool {
+    const KEY: [u8; 9] = [7, 5, 3, 2, 1, 7, 5, 3, 2];
+    let digits = digits(local_value);
+    let (base, check) = digits.split_at(digits.len() - 1);
+    let key = &KEY[KEY.len() - base.len()..];
+
+    mod11(base, key) * 10 % 11 % 10 == check[0]
+}
+
+/// Slovenia: mod 11 over the first seven digits with weights 8 down to 2.
+/// A computed check of 10 is written as 0; a remainder of 0 has no valid check digit.
+pub fn si(local_value: &str) -> bool {
+    const WEIGHTS: [u8; 7] = [8, 7, 6, 5, 4, 3, 2];
+    let digits = digits(local_value);
+    let check = match mod11(&digits, &WEIGHTS) {
+        0 => return false,
+        remainder => (11 - remainder) % 10,
+    };
+
+    check == digits[7]
+}
+
+/// Slovakia: the whole number must be divisible by 11, must not start with 0 and its third digit
+/// must be one of 2, 3, 4, 7, 8 or 9.
+pub fn sk(local_value: &str) -> bool {
+    let digits = digits(local_value);
+    if digits[0] == 0 || ![2, 3, 4, 7, 8, 9].contains(&digits[2]) {
+        return false;
+    }
+
+    local_value.parse::<u64>().is_ok_and(|number| number.is_multiple_of(11))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "eu_vat")]
+    #[test]
+    fn test_at_checksum() {
+        assert!(at("U13585627"));
+        assert!(at("U10223006"));
+        assert!(!at("U13585628"));
+        assert!(!at("U10223007"));
+    }
+
+    #[cfg(feature = "eu_vat")]
+    #[test]
+    fn test_bg_checksum() {
+        assert!(bg("175074752"));
+        assert!(bg("101004508"));
+        assert!(!bg("175074753"));
+        // First pass yields 10, second pass is used
+        assert!(bg("100000086"));
+        assert!(!bg("100000080"));
+        // 10 digit forms are not checksum validated
+        assert!(bg("1234567890"));
+    }
+
+    #[cfg(feature = "eu_vat")]
+    #[test]
+    fn test_cy_checksum() {
+        assert!(cy("10259033P"));
+        assert!(cy("00532445O"));
+        assert!(!cy("10259033Q"));
+        // Reserved prefix
+        assert!(!cy("12000000F"));
+    }
+
+    #[cfg(feature = "eu_vat")]
+    #[test]
+    fn test_cz_checksum() {
+        assert!(cz("25123891"));
+        assert!(!cz("25123890"));
+        // Remainder of 0 maps to 1, remainder of 1 maps to 0
+        assert!(cz("20000031"));
+        assert!(cz("20000090"));
+        // 9 and 10 digit forms are not checksum validated
+        assert!(cz("123456789"));
+        assert!(cz("1234567890"));
+    }
+
+    #[cfg(feature = "eu_vat")]
+    #[test]
+    fn test_de_checksum() {
+        assert!(de("136695976"));
+        assert!(de("129273398"));
+        assert!(de("811569869"));
+        assert!(!de("136695977"));
+        assert!(!de("123456789"));
+    }
+
+    #[cfg(feature = "eu_vat")]
+    #[test]
+    fn test_dk_checksum() {
+        assert!(dk("13585628"));
+        assert!(dk("88146328"));
+        assert!(!dk("13585629"));
+        assert!(!dk("88146327"));
+    }
+
+    #[cfg(feature = "eu_vat")]
+    #[test]
+    fn test_el_checksum() {
+        assert!(el("094259216"));
+        assert!(el("094019245"));
+        assert!(!el("094259217"));
+        assert!(!el("123456789"));
+    }
+
+    #[cfg(feature = "eu_vat")]
+    #[test]
+    fn test_ee_checksum() {
+        assert!(ee("100931558"));
+        assert!(ee("100594102"));
+        // Weighted sum is a multiple of ten, so the check digit wraps to 0
+        assert!(ee("100000010"));
+        assert!(!ee("100931559"));
+        assert!(!ee("100000011"));
+    }
+
+    #[cfg(feature = "eu_vat")]
+    #[test]
+    fn test_fi_checksum() {
+        assert!(fi("20774740"));
+        assert!(fi("10000020"));
+        assert!(!fi("20774741"));
+        // Remainder of 1, no check digit can make it valid
+        for check in 0..10 {
+            assert!(!fi(&format!("1000008{}", check)));
+        }
+    }
+
+    #[cfg(feature = "eu_vat")]
+    #[test]
+    fn test_hu_checksum() {
+        assert!(hu("12892312"));
+        assert!(hu("10597190"));
+        assert!(!hu("12892313"));
+        assert!(!hu("10597191"));
+    }
+
+    #[cfg(feature = "eu_vat")]
+    #[test]
+    fn test_ie_checksum() {
+        assert!(ie("6433435F"));
+        assert!(ie("6433435OA"));
+        assert!(ie("3628739UA"));
+        assert!(!ie("6433435G"));
+        assert!(!ie("6433435OB"));
+        // Old style
+        assert!(ie("8D79739I"));
+        assert!(ie("7A12345J"));
+        assert!(!ie("8D79739J"));
+    }
+
+    #[cfg(feature = "eu_vat")]
+    #[test]
+    fn test_lt_checksum() {
+        assert!(lt("119511515"));
+        assert!(lt("213179412"));
+        assert!(lt("100001919017"));
+        assert!(lt("290061371314"));
+        assert!(!lt("119511516"));
+        assert!(!lt("100001919018"));
+        // First pass yields 10, second pass is used
+        assert!(lt("100001414"));
+        assert!(lt("100000000716"));
+        assert!(!lt("100001410"));
+    }
+
+    #[cfg(feature = "eu_vat")]
+    #[test]
+    fn test_lu_checksum() {
+        assert!(lu("15027442"));
+        assert!(lu("00000000"));
+        assert!(!lu("15027443"));
+        assert!(!lu("15027424"));
+    }
+
+    #[cfg(feature = "eu_vat")]
+    #[test]
+    fn test_lv_checksum() {
+        assert!(lv("40003521600"));
+        assert!(lv("40003009497"));
+        assert!(!lv("40003521601"));
+        assert!(!lv("90000029021"));
+        // Natural persons are not checksum validated
+        assert!(lv("16117519997"));
+    }
+
+    #[cfg(feature = "eu_vat")]
+    #[test]
+    fn test_mt_checksum() {
+        assert!(mt("11679112"));
+        assert!(mt("20200019"));
+        assert!(mt("15121333"));
+        assert!(!mt("11679113"));
+        assert!(!mt("11679121"));
+    }
+
+    #[cfg(feature = "eu_vat")]
+    #[test]
+    fn test_pl_checksum() {
+        assert!(pl("8567346215"));
+        assert!(pl("5260250274"));
+        assert!(!pl("8567346216"));
+        // Remainder of 10, no check digit can make it valid
+        for check in 0..10 {
+            assert!(!pl(&format!("100000016{}", check)));
+        }
+    }
+
+    #[cfg(feature = "eu_vat")]
+    #[test]
+    fn test_pt_checksum() {
+        assert!(pt("501964843"));
+        assert!(!pt("501964844"));
+        // Computed check of 10 maps to 0
+        assert!(pt("500000000"));
+        assert!(!pt("500000001"));
+        // Computed check of 11 maps to 0
+        assert!(pt("500000050"));
+    }
+
+    #[cfg(feature = "eu_vat")]
+    #[test]
+    fn test_ro_checksum() {
+        assert!(ro("18547290"));
+        assert!(ro("14399840"));
+        assert!(!ro("18547291"));
+        assert!(!ro("14399841"));
+    }
+
+    #[cfg(feature = "eu_vat")]
+    #[test]
+    fn test_si_checksum() {
+        assert!(si("50223054"));
+        assert!(!si("50223055"));
+        // Computed check of 10 maps to 0
+        assert!(si("50000080"));
+        assert!(!si("50000081"));
+        // Remainder of 0, no check digit can make it valid
+        for check in 0..10 {
+            assert!(!si(&format!("5000002{}", check)));
+        }
+    }
+
+    #[cfg(feature = "eu_vat")]
+    #[test]
+    fn test_sk_checksum() {
+        assert!(sk("2022749619"));
+        assert!(sk("4030000007"));
+        assert!(!sk("2022749618"));
+        // Divisible by 11, but the third digit is not allowed
+        assert!(!sk("2010000003"));
+        // Divisible by 11, but starts with 0
+        assert!(!sk("0230000001"));
+    }
+}
@@ -1,16 +1,48 @@
+mod checksum;
 mod syntax;
 mod vies;
 
 use std::collections::HashMap;
 use lazy_static::lazy_static;
 use regex::Regex;
-use syntax::EU_VAT_PATTERNS;
+use syntax::{EU_VAT_EXAMPLES, EU_VAT_PATTERNS};
 use crate::TaxIdType;
+use crate::errors::ValidationError;
 use crate::verification::{Verifier};
 
 #[derive(Debug)]
 pub struct EuVat;
 
+impl EuVat {
+    fn checksum(tax_country_code: &str) -> Option<fn(&str) -> bool> {
+        let checksum: fn(&str) -> bool = match tax_country_code {
+            "AT" => checksum::at,
+            "BG" => checksum::bg,
+            "CY" => checksum::cy,
+            "CZ" => checksum::cz,
+            "DE" => checksum::de,
+            "DK" => checksum::dk,
+            "EE" => checksum::ee,
+            "EL" => checksum::el,
+            "FI" => checksum::fi,
+            "HU" => checksum::hu,
+            "IE" => checksum::ie,
+            "LT" => checksum::lt,
+            "LU" => checksum::lu,
+            "LV" => checksum::lv,
+            "MT" => checksum::mt,
+            "PL" => checksum::pl,
+            "PT" => checksum::pt,
+            "RO" => checksum::ro,
+            "SI" => checksum::si,
+            "SK" => checksum::sk,
+            _ => return None,
+        };
+
+        Some(checksum)
+    }
+}
+
 lazy_static! {
     #[derive(Debug)]
     pub static ref COUNTRIES: Vec<&'static str> = vec![
@@ -24,10 +56,77 @@ impl TaxIdType for EuVat {
         "eu_vat"
     }
 
-    fn syntax_map(&self) -> &HashMap<String, Regex> {
+    fn human_name(&self) -> &'static str {
+        "EU VAT number"
+    }
+
+    fn example(&self, tax_country_code: &str) -> Option<&'static str> {
+        EU_VAT_EXAMPLES.get(tax_country_code).copied()
+    }
+
+    fn local_value_lengths(&self, tax_country_code: &str) -> Option<&'static [usize]> {
+        let lengths: &'static [usize] = match tax_country_code {
+            "AT" => &[9],
+            "BE" => &[10],
+            "BG" => &[9, 10],
+            "CY" => &[9],
+            "CZ" => &[8, 9, 10],
+            "DE" => &[9],
+            "DK" => &[8],
+            "EE" => &[9],
+            "EL" => &[9],
+            "ES" => &[9],
+            "FI" => &[8],
+            "FR" => &[11],
+            "HR" => &[11],
+            "HU" => &[8],
+            "IE" => &[8, 9],
+            "IT" => &[11],
+            "LT" => &[9, 12],
+            "LU" => &[8],
+            "LV" => &[11],
+            "MT" => &[8],
+            "NL" => &[12],
+            "PL" => &[10],
+            "PT" => &[9],
+            "RO" => &[2, 3, 4, 5, 6, 7, 8, 9, 10],
+            "SE" => &[12],
+            "SI" => &[8],
+            "SK" => &[10],
+            "XI" => &[9, 12, 5],
+            _ => return None,
+        };
+
+        Some(lengths)
+    }
+
+    fn syntax_map(&self) -> &'static HashMap<String, Regex> {
         &EU_VAT_PATTERNS
     }
 
+    fn validate_checksum(&self, value: &str) -> Result<(), ValidationError> {
+        let (tax_country_code, local_value) = value.split_at(2);
+        match EuVat::checksum(tax_country_code) {
+            Some(is_valid) if !is_valid(local_value) => Err(ValidationError::InvalidChecksum),
+            _ => Ok(()),
+        }
+    }
+
+    fn has_checksum(&self, tax_country_code: &str) -> bool {
+        EuVat::checksum(tax_country_code).is_some()
+    }
+
+    /// NL ends in B and a 2 digit sequence, SE in a 2 digit sequence (01 for the head office)
+    /// and the 12 digit XI form in a 3 digit branch identifier.
+    fn branch_suffix<'a>(&self, tax_country_code: &str, local_value: &'a str) -> Option<&'a str> {
+        match tax_country_code {
+            "NL" => local_value.get(9..),
+            "SE" => local_value.get(10..),
+            "XI" if local_value.len() == 12 => local_value.get(9..),
+            _ => None,
+        }
+    }
+
     fn country_code_from_tax_country(&self, tax_country_code: &str) -> String {
         let country_code = match tax_country_code {
             "XI" => "GB",
@@ -38,14 +137,13 @@ impl TaxIdType for EuVat {
         country_code.to_string()
     }
 
-    fn verifier(&self) -> Box<dyn Verifier> {
-        Box::new(vies::Vies)
+    fn verifier(&self) -> &'static dyn Verifier {
+        &vies::Vies
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::errors::ValidationError;
     use super::*;
 
     fn assert_validations(valid_vat_numbers: Vec<&str>, invalid_vat_numbers: Vec<&str>) {
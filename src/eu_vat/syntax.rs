@@ -36,6 +36,41 @@ lazy_static! {
         m.insert("XI".to_string(), Regex::new(r"^XI([0-9]{9}|[0-9]{12}|(HA|GD)[0-9]{3})$").unwrap());
         m
     };
+
+    /// A valid, checksum-passing example per tax country code, used for onboarding hints.
+    #[derive(Debug)]
+    pub static ref EU_VAT_EXAMPLES: HashMap<&'static str, &'static str> = {
+        let mut m = HashMap::new();
+        m.insert("AT", "ATU13585627");
+        m.insert("BE", "BE0776091951");
+        m.insert("BG", "BG175074752");
+        m.insert("CY", "CY10259033P");
+        m.insert("CZ", "CZ25123891");
+        m.insert("DE", "DE136695976");
+        m.insert("DK", "DK13585628");
+        m.insert("EE", "EE100931558");
+        m.insert("EL", "EL094259216");
+        m.insert("ES", "ESA28015865");
+        m.insert("FI", "FI20774740");
+        m.insert("FR", "FR40303265045");
+        m.insert("HR", "HR33392005961");
+        m.insert("HU", "HU12892312");
+        m.insert("IE", "IE6433435F");
+        m.insert("IT", "IT00743110157");
+        m.insert("LT", "LT119511515");
+        m.insert("LU", "LU15027442");
+        m.insert("LV", "LV40003521600");
+        m.insert("MT", "MT11679112");
+        m.insert("NL", "NL004495445B01");
+        m.insert("PL", "PL8567346215");
+        m.insert("PT", "PT501964843");
+        m.insert("RO", "RO18547290");
+        m.insert("SE", "SE556703748501");
+        m.insert("SI", "SI50223054");
+        m.insert("SK", "SK2022749619");
+        m.insert("XI", "XI591819014");
+        m
+    };
 }
 
 #[cfg(test)]
@@ -50,4 +85,12 @@ mod tests {
         eu_regex_countries.sort();
         assert_eq!(eu_regex_countries, *COUNTRIES);
     }
+
+    #[cfg(feature = "eu_vat")]
+    #[test]
+    fn test_each_eu_country_has_an_example() {
+        let mut eu_example_countries = EU_VAT_EXAMPLES.keys().copied().collect::<Vec<&str>>();
+        eu_example_countries.sort();
+        assert_eq!(eu_example_countries, *COUNTRIES);
+    }
 }
\ No newline at end of file
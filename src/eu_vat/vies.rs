@@ -1,24 +1,28 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use lazy_static::lazy_static;
 
-use roxmltree;
+use reqwest::header::USER_AGENT;
 use serde_json::json;
 
 use crate::errors::VerificationError;
 use crate::TaxId;
-use crate::verification::{Verification, VerificationResponse, VerificationStatus, UnavailableReason, Verifier};
+#[cfg(target_arch = "wasm32")]
+use crate::verification::ResponseFuture;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::verification::blocking_client;
+use crate::verification::{record_endpoint, user_agent, VerificationConfig, Verification, VerificationResponse, VerificationStatus, UnavailableReason, Verifier, Provider};
 use crate::verification::UnavailableReason::{*};
 
 // INFO(2024-05-08 mollemoll):
 // Data from Vies
 // https://ec.europa.eu/taxation_customs/vies/checkVatService.wsdl
 
-static URI: &'static str = "http://ec.europa.eu/taxation_customs/vies/services/checkVatService";
-static ENVELOPE: &'static str = "
+static URI: &str = "http://ec.europa.eu/taxation_customs/vies/services/checkVatService";
+static ENVELOPE: &str = "
 <soapenv:Envelope xmlns:soapenv=\"http://schemas.xmlsoap.org/soap/envelope/\" xmlns:v1=\"http://schemas.conversesolutions.com/xsd/dmticta/v1\">
     <soapenv:Header/>
     <soapenv:Body>
-        <checkVat xmlns=\"urn:ec.europa.eu:taxud:vies:services:checkVat:types\">
+        <checkVat xmlns=\"{namespace}\">
             <countryCode>{country}</countryCode>
             <vatNumber>{number}</vatNumber>
         </checkVat>
@@ -26,6 +30,8 @@ static ENVELOPE: &'static str = "
 </soapenv:Envelope>
 ";
 
+static NAMESPACE: &str = "urn:ec.europa.eu:taxud:vies:services:checkVat:types";
+
 // Vies FAULT codes
 const SERVICE_UNAVAILABLE: &str = "SERVICE_UNAVAILABLE";
 const MS_UNAVAILABLE: &str = "MS_UNAVAILABLE";
@@ -59,8 +65,32 @@ lazy_static! {
 pub struct Vies;
 
 impl Vies {
+    #[cfg(not(target_arch = "wasm32"))]
+    fn blocking_request(tax_id: &TaxId, config: &VerificationConfig) -> Result<reqwest::blocking::RequestBuilder, VerificationError> {
+        let body = ENVELOPE
+            .replace("{namespace}", NAMESPACE)
+            .replace("{country}", tax_id.tax_country_code())
+            .replace("{number}", tax_id.local_value());
+        let uri = Vies::uri(tax_id, config);
+        record_endpoint(uri);
+        let request = blocking_client(config)?
+            .post(uri)
+            .header(USER_AGENT, user_agent(config))
+            .header("Content-Type", "text/xml")
+            .body(body);
+
+        Ok(request)
+    }
+
+    fn uri<'a>(tax_id: &TaxId, config: &'a VerificationConfig) -> &'a str {
+        config.endpoints.ms_endpoint_overrides.get(tax_id.tax_country_code())
+            .or(config.endpoints.vies.as_ref())
+            .map_or(URI, String::as_str)
+    }
+
     fn xml_to_hash(xml: &roxmltree::Document) -> HashMap<String, Option<String>> {
         let mut hash = HashMap::new();
+        let mut in_namespace = HashSet::new();
         let tags_to_exclude = ["Body", "Envelope", "Fault"];
 
         for node in xml.descendants() {
@@ -69,6 +99,14 @@ impl Vies {
                 continue;
             }
 
+            // Tags are flattened by local name, an element of the checkVat namespace is
+            // never shadowed by a namesake from another namespace
+            if node.tag_name().namespace() == Some(NAMESPACE) {
+                in_namespace.insert(tag_name);
+            } else if in_namespace.contains(tag_name) {
+                continue;
+            }
+
             if let Some(text) = node.text() {
                 // Absence of data is represented by "---" in VIES
                 if text == "---" {
@@ -81,18 +119,44 @@ impl Vies {
 
         hash
     }
+
+    /// The fault code of a SOAP Fault, whatever prefix the envelope uses (`env:`, `soap:`,
+    /// `soapenv:`, ...). A known fault code anywhere inside the Fault wins, i.e. when it's
+    /// nested inside `detail` with a generic `faultstring` next to it.
+    fn fault(xml: &roxmltree::Document) -> Option<String> {
+        let fault = xml.descendants().find(|node| node.tag_name().name() == "Fault")?;
+        let texts: Vec<(&str, &str)> = fault.descendants()
+            .filter(|node| node.is_element())
+            .filter_map(|node| node.text().map(|text| (node.tag_name().name(), text.trim())))
+            .filter(|(_, text)| !text.is_empty())
+            .collect();
+
+        let known = texts.iter()
+            .map(|(_, text)| text.rsplit_once(':').map_or(*text, |(_, code)| code))
+            .find(|code| FAULT_MAP.contains_key(code));
+        let reported = || ["faultstring", "faultcode"].iter()
+            .find_map(|tag| texts.iter().find(|(name, _)| name == tag))
+            .map(|(_, text)| *text);
+
+        known.or_else(reported).map(str::to_string)
+    }
+
+    /// Whether the fault is blamed on the server (SOAP 1.1 `Server`, SOAP 1.2 `Receiver`),
+    /// as opposed to the request, which won't succeed when retried.
+    fn is_server_fault(xml: &roxmltree::Document) -> bool {
+        xml.descendants()
+            .find(|node| node.tag_name().name() == "Fault")
+            .and_then(|fault| fault.descendants().find(|node| matches!(node.tag_name().name(), "faultcode" | "Value")))
+            .and_then(|node| node.text())
+            .map(|text| text.trim().rsplit_once(':').map_or(text.trim(), |(_, code)| code))
+            .is_some_and(|code| code == "Server" || code == "Receiver")
+    }
 }
 
 impl Verifier for Vies {
-    fn make_request(&self, tax_id: &TaxId) -> Result<VerificationResponse, VerificationError> {
-        let client = reqwest::blocking::Client::new();
-        let body = ENVELOPE
-            .replace("{country}", tax_id.tax_country_code())
-            .replace("{number}", tax_id.local_value());
-        let res = client
-            .post(URI)
-            .header("Content-Type", "text/xml")
-            .body(body)
+    #[cfg(not(target_arch = "wasm32"))]
+    fn make_request(&self, tax_id: &TaxId, config: &VerificationConfig) -> Result<VerificationResponse, VerificationError> {
+        let res = Vies::blocking_request(tax_id, config)?
             .send()
             .map_err(VerificationError::HttpError)?;
 
@@ -104,22 +168,67 @@ impl Verifier for Vies {
         )
     }
 
+    #[cfg(target_arch = "wasm32")]
+    fn make_request_async<'a>(&'a self, tax_id: &'a TaxId, config: &'a VerificationConfig) -> ResponseFuture<'a> {
+        Box::pin(async move {
+            let body = ENVELOPE
+                .replace("{namespace}", NAMESPACE)
+                .replace("{country}", tax_id.tax_country_code())
+                .replace("{number}", tax_id.local_value());
+            let uri = Vies::uri(tax_id, config);
+            record_endpoint(uri);
+            let res = reqwest::Client::new()
+                .post(uri)
+                .header(USER_AGENT, user_agent(config))
+                .header("Content-Type", "text/xml")
+                .body(body)
+                .send()
+                .await
+                .map_err(VerificationError::HttpError)?;
+
+            Ok(
+                VerificationResponse::new(
+                    res.status().as_u16(),
+                    res.text().await.map_err(VerificationError::HttpError)?
+                )
+            )
+        })
+    }
+
+    fn provider(&self) -> Provider {
+        Provider::Vies
+    }
+
+    fn endpoint(&self, tax_id: &TaxId, config: &VerificationConfig) -> String {
+        Vies::uri(tax_id, config).to_string()
+    }
+
     fn parse_response(&self, response: VerificationResponse) -> Result<Verification, VerificationError> {
-        let doc = roxmltree::Document::parse(response.body()).map_err(VerificationError::XmlParsingError)?;
-        let hash = Vies::xml_to_hash(&doc);
-        let fault_string = hash.get("faultstring")
-            .and_then(|x| x.as_deref());
+        // VIES occasionally answers with an empty body during partial outages
+        if response.body().trim().is_empty() {
+            return Ok(Verification::new(
+                VerificationStatus::Unavailable(ServiceUnavailable),
+                json!({})
+            ));
+        }
+
+        let doc = roxmltree::Document::parse(response.body())
+            .map_err(|e| VerificationError::xml("VIES", response.body(), e))?;
+        let mut hash = Vies::xml_to_hash(&doc);
+        let fault = Vies::fault(&doc);
 
-        let verification_status = match fault_string {
+        let verification_status = match fault {
             Some(fault) => {
-                match FAULT_MAP.get(fault){
-                    Some(reason) => VerificationStatus::Unavailable(*reason),
-                    None => {
-                        return Err(VerificationError::UnexpectedResponse(
-                            format!("Unknown fault code: {}", fault)
-                        ));
-                    }
-                }
+                let status = match FAULT_MAP.get(fault.as_str()) {
+                    Some(reason) => VerificationStatus::Unavailable(reason.clone()),
+                    // Unknown server faults are likely transient, client faults won't go away on retry
+                    None if Vies::is_server_fault(&doc) => VerificationStatus::Unavailable(Other(fault.clone())),
+                    None => return Err(VerificationError::UnexpectedResponse(
+                        format!("Unknown fault code: {}", fault)
+                    )),
+                };
+                hash.insert("faultstring".to_string(), Some(fault));
+                status
             }
             None => {
                 let validity_value = hash.get("valid")
@@ -155,6 +264,89 @@ impl Verifier for Vies {
 mod tests {
     use super::*;
 
+    #[cfg(feature = "eu_vat")]
+    #[test]
+    fn test_make_request_through_proxy() {
+        use std::io::{BufRead, BufReader, Write};
+        use std::net::TcpListener;
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let proxy_uri = format!("http://{}", listener.local_addr().unwrap());
+        let proxy = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut request_line = String::new();
+            BufReader::new(&stream).read_line(&mut request_line).unwrap();
+            (&stream).write_all(b"HTTP/1.1 503 Service Unavailable\r\nContent-Length: 0\r\n\r\n").unwrap();
+            request_line
+        });
+
+        let tax_id = TaxId::new("SE123456789101").unwrap();
+        let config = VerificationConfig {
+            proxy: Some(reqwest::Proxy::http(proxy_uri).unwrap()),
+            ..Default::default()
+        };
+        let response = Vies.make_request(&tax_id, &config).unwrap();
+
+        assert_eq!(response.status(), 503);
+        assert_eq!(proxy.join().unwrap(), format!("POST {} HTTP/1.1\r\n", URI));
+    }
+
+    #[cfg(feature = "eu_vat")]
+    #[test]
+    fn test_request_endpoint() {
+        let tax_id = TaxId::new("SE123456789101").unwrap();
+        let request = Vies::blocking_request(&tax_id, &VerificationConfig::default()).unwrap().build().unwrap();
+        assert_eq!(request.url().as_str(), URI);
+        let body = std::str::from_utf8(request.body().unwrap().as_bytes().unwrap()).unwrap();
+        assert!(body.contains("<checkVat xmlns=\"urn:ec.europa.eu:taxud:vies:services:checkVat:types\">"));
+
+        let config = VerificationConfig {
+            endpoints: crate::Endpoints {
+                vies: Some("https://ec.europa.eu/taxation_customs/vies/services/checkVatService".to_string()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let request = Vies::blocking_request(&tax_id, &config).unwrap().build().unwrap();
+        assert_eq!(request.url().as_str(), "https://ec.europa.eu/taxation_customs/vies/services/checkVatService");
+    }
+
+    #[cfg(feature = "eu_vat")]
+    #[test]
+    fn test_request_member_state_endpoint() {
+        let config = VerificationConfig {
+            endpoints: crate::Endpoints {
+                ms_endpoint_overrides: HashMap::from([
+                    ("DE".to_string(), "https://evatr.example.de/checkVatService".to_string()),
+                ]),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let tax_id = TaxId::new("DE136695976").unwrap();
+        let request = Vies::blocking_request(&tax_id, &config).unwrap().build().unwrap();
+        assert_eq!(request.url().as_str(), "https://evatr.example.de/checkVatService");
+
+        let tax_id = TaxId::new("SE556703748501").unwrap();
+        let request = Vies::blocking_request(&tax_id, &config).unwrap().build().unwrap();
+        assert_eq!(request.url().as_str(), URI);
+        assert_eq!(tax_id.verification_endpoint_with_config(&config), URI);
+    }
+
+    #[cfg(feature = "eu_vat")]
+    #[test]
+    fn test_request_user_agent() {
+        let tax_id = TaxId::new("SE123456789101").unwrap();
+
+        let request = Vies::blocking_request(&tax_id, &VerificationConfig::default()).unwrap().build().unwrap();
+        assert_eq!(request.headers()[USER_AGENT], concat!("tax-ids/", env!("CARGO_PKG_VERSION")));
+
+        let config = VerificationConfig { user_agent: Some("my-shop/1.0".to_string()), ..Default::default() };
+        let request = Vies::blocking_request(&tax_id, &config).unwrap().build().unwrap();
+        assert_eq!(request.headers()[USER_AGENT], "my-shop/1.0");
+    }
+
     #[cfg(feature = "eu_vat")]
     #[test]
     fn test_xml_to_hash() {
@@ -184,6 +376,37 @@ mod tests {
         assert_eq!(hash.get("address"), Some(&None));
     }
 
+    #[cfg(feature = "eu_vat")]
+    #[test]
+    fn test_xml_to_hash_namesake_in_other_namespace() {
+        let xml = r#"
+            <env:Envelope xmlns:env="http://schemas.xmlsoap.org/soap/envelope/">
+                <env:Header>
+                    <valid xmlns="urn:example:decoy">false</valid>
+                </env:Header>
+                <env:Body>
+                    <ns2:checkVatResponse xmlns:ns2="urn:ec.europa.eu:taxud:vies:services:checkVat:types">
+                        <ns2:countryCode>SE</ns2:countryCode>
+                        <ns2:vatNumber>123456789101</ns2:vatNumber>
+                        <ns2:valid>true</ns2:valid>
+                    </ns2:checkVatResponse>
+                    <decoy:extension xmlns:decoy="urn:example:decoy">
+                        <decoy:valid>false</decoy:valid>
+                        <decoy:countryCode>XX</decoy:countryCode>
+                    </decoy:extension>
+                </env:Body>
+            </env:Envelope>
+        "#;
+        let doc = roxmltree::Document::parse(xml).unwrap();
+        let hash = Vies::xml_to_hash(&doc);
+
+        assert_eq!(hash.get("valid"), Some(&Some("true".to_string())));
+        assert_eq!(hash.get("countryCode"), Some(&Some("SE".to_string())));
+
+        let verification = Vies.parse_response(VerificationResponse::new(200, xml.to_string())).unwrap();
+        assert_eq!(verification.status(), &VerificationStatus::Verified);
+    }
+
     #[cfg(feature = "eu_vat")]
     #[test]
     fn test_parse_response_verified() {
@@ -209,6 +432,65 @@ mod tests {
         let verification = verifier.parse_response(response).unwrap();
 
         assert_eq!(verification.status(), &VerificationStatus::Verified);
+        assert_eq!(
+            verification.provider_date().map(|date| date.to_rfc3339()),
+            Some("2021-01-01T00:00:00+01:00".to_string())
+        );
+    }
+
+    #[cfg(feature = "eu_vat")]
+    #[test]
+    fn test_parse_verification() {
+        let body = r#"
+            <soapenv:Envelope xmlns:soapenv="http://schemas.xmlsoap.org/soap/envelope/">
+                <soapenv:Body>
+                    <checkVat xmlns="urn:ec.europa.eu:taxud:vies:services:checkVat:types">
+                        <countryCode>SE</countryCode>
+                        <vatNumber>556703748501</vatNumber>
+                        <requestDate>2021-01-01+01:00</requestDate>
+                        <valid>true</valid>
+                        <name>Spotify AB</name>
+                        <address>Regeringsgatan 19</address>
+                    </checkVat>
+                </soapenv:Body>
+            </soapenv:Envelope>
+        "#;
+        let tax_id = TaxId::new("SE556703748501").unwrap();
+
+        let verification = tax_id.parse_verification(200, body).unwrap();
+        assert_eq!(verification.status(), &VerificationStatus::Verified);
+        assert_eq!(verification.data()["name"], "Spotify AB");
+        assert_eq!(verification.provider(), Provider::Vies);
+        assert!(verification.has_trader_details());
+
+        let verification = tax_id.parse_verification(200, &body.replace("<valid>true", "<valid>false")).unwrap();
+        assert_eq!(verification.status(), &VerificationStatus::Unverified);
+    }
+
+    #[cfg(feature = "eu_vat")]
+    #[test]
+    fn test_parse_response_verified_without_trader_details() {
+        let response = VerificationResponse::new(
+            200,
+            r#"
+                <soapenv:Envelope xmlns:soapenv="http://schemas.xmlsoap.org/soap/envelope/">
+                    <soapenv:Body>
+                        <checkVat xmlns="urn:ec.europa.eu:taxud:vies:services:checkVat:types">
+                            <countryCode>DE</countryCode>
+                            <vatNumber>136695976</vatNumber>
+                            <requestDate>2021-01-01+01:00</requestDate>
+                            <valid>true</valid>
+                            <name>---</name>
+                            <address>---</address>
+                        </checkVat>
+                    </soapenv:Body>
+                </soapenv:Envelope>
+            "#.to_string()
+        );
+        let verification = Vies.parse_response(response).unwrap();
+
+        assert_eq!(verification.status(), &VerificationStatus::Verified);
+        assert!(!verification.has_trader_details());
     }
 
     #[cfg(feature = "eu_vat")]
@@ -265,6 +547,153 @@ mod tests {
         }));
     }
 
+    #[cfg(feature = "eu_vat")]
+    #[test]
+    fn test_parse_response_unavailable_soap_prefix() {
+        let response = VerificationResponse::new(
+            500,
+            r#"
+                <soap:Envelope xmlns:soap="http://schemas.xmlsoap.org/soap/envelope/">
+                    <soap:Body>
+                        <soap:Fault>
+                            <faultcode>soap:Server</faultcode>
+                            <faultstring>MS_UNAVAILABLE</faultstring>
+                        </soap:Fault>
+                    </soap:Body>
+                </soap:Envelope>
+            "#.to_string()
+        );
+        let verifier = Vies;
+        let verification = verifier.parse_response(response).unwrap();
+
+        assert_eq!(verification.status(), &VerificationStatus::Unavailable(ServiceUnavailable));
+        assert_eq!(verification.failure_reason(), Some("MS_UNAVAILABLE".to_string()));
+    }
+
+    #[cfg(feature = "eu_vat")]
+    #[test]
+    fn test_parse_response_unavailable_nested_detail() {
+        let response = VerificationResponse::new(
+            500,
+            r#"
+                <soapenv:Envelope xmlns:soapenv="http://schemas.xmlsoap.org/soap/envelope/">
+                    <soapenv:Body>
+                        <soapenv:Fault>
+                            <faultcode>soapenv:Server</faultcode>
+                            <faultstring>Server error</faultstring>
+                            <detail>
+                                <ns2:checkVatFault xmlns:ns2="urn:ec.europa.eu:taxud:vies:services:checkVat:types">
+                                    <ns2:faultcode>ns2:TIMEOUT</ns2:faultcode>
+                                </ns2:checkVatFault>
+                            </detail>
+                        </soapenv:Fault>
+                    </soapenv:Body>
+                </soapenv:Envelope>
+            "#.to_string()
+        );
+        let verifier = Vies;
+        let verification = verifier.parse_response(response).unwrap();
+
+        assert_eq!(verification.status(), &VerificationStatus::Unavailable(Timeout));
+        assert_eq!(verification.data()["faultstring"], TIMEOUT);
+    }
+
+    #[cfg(feature = "eu_vat")]
+    #[test]
+    fn test_parse_response_fault_without_faultstring() {
+        let response = VerificationResponse::new(
+            500,
+            r#"
+                <env:Envelope xmlns:env="http://schemas.xmlsoap.org/soap/envelope/">
+                    <env:Body>
+                        <env:Fault>
+                            <faultcode>env:Server</faultcode>
+                        </env:Fault>
+                    </env:Body>
+                </env:Envelope>
+            "#.to_string()
+        );
+        let verifier = Vies;
+        let verification = verifier.parse_response(response).unwrap();
+
+        assert_eq!(verification.status(), &VerificationStatus::Unavailable(Other("env:Server".to_string())));
+    }
+
+    #[cfg(feature = "eu_vat")]
+    #[test]
+    fn test_parse_response_client_fault() {
+        for fault in ["INVALID_INPUT", "INVALID_REQUESTER_INFO"] {
+            let response = VerificationResponse::new(
+                500,
+                format!(r#"
+                    <env:Envelope xmlns:env="http://schemas.xmlsoap.org/soap/envelope/">
+                        <env:Body>
+                            <env:Fault>
+                                <faultcode>env:Client</faultcode>
+                                <faultstring>{}</faultstring>
+                            </env:Fault>
+                        </env:Body>
+                    </env:Envelope>
+                "#, fault)
+            );
+            let verifier = Vies;
+
+            match verifier.parse_response(response) {
+                Err(VerificationError::UnexpectedResponse(msg)) => assert_eq!(msg, format!("Unknown fault code: {}", fault)),
+                other => panic!("Expected UnexpectedResponse error, got {:?}", other),
+            }
+        }
+    }
+
+    #[cfg(feature = "eu_vat")]
+    #[test]
+    fn test_parse_response_unknown_fault() {
+        let response = VerificationResponse::new(
+            500,
+            r#"
+                <env:Envelope xmlns:env="http://schemas.xmlsoap.org/soap/envelope/">
+                    <env:Header/>
+                    <env:Body>
+                        <env:Fault>
+                            <faultcode>env:Server</faultcode>
+                            <faultstring>MS_MAINTENANCE_WINDOW</faultstring>
+                        </env:Fault>
+                    </env:Body>
+                </env:Envelope>
+            "#.to_string()
+        );
+        let verifier = Vies;
+        let verification = verifier.parse_response(response).unwrap();
+
+        assert_eq!(
+            verification.status(),
+            &VerificationStatus::Unavailable(UnavailableReason::Other("MS_MAINTENANCE_WINDOW".to_string()))
+        );
+    }
+
+    #[cfg(feature = "eu_vat")]
+    #[test]
+    fn test_parse_response_empty_body() {
+        for body in ["", "  \n\t  "] {
+            let response = VerificationResponse::new(200, body.to_string());
+            let verifier = Vies;
+            let verification = verifier.parse_response(response).unwrap();
+
+            assert_eq!(verification.status(), &VerificationStatus::Unavailable(ServiceUnavailable));
+            assert_eq!(verification.data(), &json!({}));
+        }
+    }
+
+    #[cfg(feature = "eu_vat")]
+    #[test]
+    fn test_parse_response_malformed_xml() {
+        let response = VerificationResponse::new(200, "<env:Envelope><faultstring>".to_string());
+        let verifier = Vies;
+        let verification = verifier.parse_response(response);
+
+        assert!(matches!(verification, Err(VerificationError::XmlParsingError { .. })));
+    }
+
     #[cfg(feature = "eu_vat")]
     #[test]
     fn test_parse_response_missing_valid_field() {
@@ -1,16 +1,22 @@
-use serde_json::json;
+use reqwest::header::{RETRY_AFTER, USER_AGENT};
+use serde_json::{json, Value};
 use crate::errors::VerificationError;
 use crate::TaxId;
-use crate::verification::{Verification, VerificationResponse, VerificationStatus::{*}, Verifier};
-use crate::verification::UnavailableReason::ServiceUnavailable;
+#[cfg(target_arch = "wasm32")]
+use crate::verification::ResponseFuture;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::verification::blocking_client;
+use crate::verification::{record_endpoint, user_agent, VerificationConfig, Verification, VerificationResponse, VerificationStatus::{*}, Verifier, Provider};
+use crate::verification::UnavailableReason::{RateLimit, ServiceUnavailable};
 
 // INFO(2024-05-08 mollemoll):
 // Data from HMRC
 // https://www.tax.service.gov.uk/check-vat-number/enter-vat-details
 // https://developer.service.hmrc.gov.uk/api-documentation/docs/api/service/vat-registered-companies-api/1.0/oas/page
 
-static BASE_URI: &'static str = "https://api.service.hmrc.gov.uk/organisations/vat/check-vat-number/lookup";
+static BASE_URI: &str = "https://api.service.hmrc.gov.uk/organisations/vat/check-vat-number/lookup";
 const NOT_FOUND: &str = "NOT_FOUND";
+const TOO_MANY_REQUESTS: u16 = 429;
 #[allow(dead_code)]
 const SERVER_ERROR: &str = "SERVER_ERROR";
 
@@ -18,34 +24,119 @@ const SERVER_ERROR: &str = "SERVER_ERROR";
 #[derive(Debug)]
 pub struct Hmrc;
 
+impl Hmrc {
+    fn retry_after(headers: &reqwest::header::HeaderMap) -> Option<String> {
+        headers.get(RETRY_AFTER)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.to_string())
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn blocking_request(tax_id: &TaxId, config: &VerificationConfig) -> Result<reqwest::blocking::RequestBuilder, VerificationError> {
+        let uri = Hmrc::uri(tax_id, config);
+        record_endpoint(&uri);
+        let request = blocking_client(config)?
+            .get(uri)
+            .header(USER_AGENT, user_agent(config))
+            .header("Accept", "application/vnd.hmrc.1.0+json");
+
+        Ok(request)
+    }
+
+    fn uri(tax_id: &TaxId, config: &VerificationConfig) -> String {
+        let base_uri = config.endpoints.hmrc.as_deref().unwrap_or(BASE_URI);
+        match &config.hmrc_requester_vrn {
+            Some(requester_vrn) => format!(
+                "{}/{}/{}",
+                base_uri,
+                tax_id.local_value(),
+                requester_vrn.trim_start_matches("GB")
+            ),
+            None => format!("{}/{}", base_uri, tax_id.local_value()),
+        }
+    }
+}
+
 impl Verifier for Hmrc {
-    fn make_request(&self, tax_id: &TaxId) -> Result<VerificationResponse, VerificationError> {
-        let client = reqwest::blocking::Client::new();
-        let res = client
-            .get(format!("{}/{}", BASE_URI, tax_id.local_value()))
-            .header("Accept", "application/vnd.hmrc.1.0+json")
+    #[cfg(not(target_arch = "wasm32"))]
+    fn make_request(&self, tax_id: &TaxId, config: &VerificationConfig) -> Result<VerificationResponse, VerificationError> {
+        let res = Hmrc::blocking_request(tax_id, config)?
             .send()
             .map_err(VerificationError::HttpError)?;
 
+        let retry_after = Hmrc::retry_after(res.headers());
+
         Ok(
             VerificationResponse::new(
                 res.status().as_u16(),
                 res.text().map_err(VerificationError::HttpError)?
-            )
+            ).with_retry_after(retry_after)
         )
     }
 
+    #[cfg(target_arch = "wasm32")]
+    fn make_request_async<'a>(&'a self, tax_id: &'a TaxId, config: &'a VerificationConfig) -> ResponseFuture<'a> {
+        Box::pin(async move {
+            let uri = Hmrc::uri(tax_id, config);
+            record_endpoint(&uri);
+            let res = reqwest::Client::new()
+                .get(uri)
+                .header(USER_AGENT, user_agent(config))
+                .header("Accept", "application/vnd.hmrc.1.0+json")
+                .send()
+                .await
+                .map_err(VerificationError::HttpError)?;
+
+            let retry_after = Hmrc::retry_after(res.headers());
+
+            Ok(
+                VerificationResponse::new(
+                    res.status().as_u16(),
+                    res.text().await.map_err(VerificationError::HttpError)?
+                ).with_retry_after(retry_after)
+            )
+        })
+    }
+
+    fn provider(&self) -> Provider {
+        Provider::Hmrc
+    }
+
+    fn endpoint(&self, tax_id: &TaxId, config: &VerificationConfig) -> String {
+        Hmrc::uri(tax_id, config)
+    }
+
     fn parse_response(&self, response: VerificationResponse) -> Result<Verification, VerificationError> {
+        if response.status() == TOO_MANY_REQUESTS {
+            // The body of a throttled request isn't guaranteed to be JSON
+            let mut data = serde_json::from_str::<Value>(response.body()).ok()
+                .filter(|v| v.is_object())
+                .unwrap_or_else(|| json!({}));
+            if let Some(retry_after) = response.retry_after() {
+                data["retryAfter"] = match retry_after.parse::<u64>() {
+                    Ok(seconds) => json!(seconds),
+                    Err(_) => json!(retry_after),
+                };
+            }
+
+            return Ok(Verification::new(Unavailable(RateLimit), data));
+        }
+
         let v: serde_json::Value = serde_json::from_str(response.body())
-            .map_err(VerificationError::JsonParsingError)?;
+            .map_err(|e| VerificationError::json("HMRC", response.body(), e))?;
         let hash = v.as_object().unwrap();
         let fault = hash.get("code").and_then(|v| v.as_str());
 
         let verification_result = match fault {
             None => {
+                let mut data = json!(hash.get("target"));
+                if let Some(consultation_number) = hash.get("consultationNumber") {
+                    data["consultationNumber"] = consultation_number.clone();
+                }
+
                 Verification::new(
                     Verified,
-                    json!(hash.get("target"))
+                    data
                 )
             },
             Some(fault_code) if fault_code == NOT_FOUND => {
@@ -70,6 +161,19 @@ impl Verifier for Hmrc {
 mod tests {
     use super::*;
 
+    #[cfg(feature = "gb_vat")]
+    #[test]
+    fn test_request_user_agent() {
+        let tax_id = TaxId::new("GB591819014").unwrap();
+
+        let request = Hmrc::blocking_request(&tax_id, &VerificationConfig::default()).unwrap().build().unwrap();
+        assert_eq!(request.headers()[USER_AGENT], concat!("tax-ids/", env!("CARGO_PKG_VERSION")));
+
+        let config = VerificationConfig { user_agent: Some("my-shop/1.0".to_string()), ..Default::default() };
+        let request = Hmrc::blocking_request(&tax_id, &config).unwrap().build().unwrap();
+        assert_eq!(request.headers()[USER_AGENT], "my-shop/1.0");
+    }
+
     #[cfg(feature = "gb_vat")]
     #[test]
     fn test_parse_response_verified() {
@@ -110,6 +214,146 @@ mod tests {
         }));
     }
 
+    #[cfg(feature = "gb_vat")]
+    #[test]
+    fn test_parse_verification() {
+        let body = r#"{
+            "target": {
+                "name": "VIRGIN ATLANTIC AIRWAYS LTD",
+                "vatNumber": "425216184"
+            },
+            "processingDate": "2024-05-06T09:18:58+01:00"
+        }"#;
+        let tax_id = TaxId::new("GB425216184").unwrap();
+
+        let verification = tax_id.parse_verification(200, body).unwrap();
+        assert_eq!(verification.status(), &Verified);
+        assert_eq!(verification.data()["name"], "VIRGIN ATLANTIC AIRWAYS LTD");
+        assert_eq!(verification.provider(), Provider::Hmrc);
+
+        let verification = tax_id.parse_verification(404, r#"{"code": "NOT_FOUND"}"#).unwrap();
+        assert_eq!(verification.status(), &Unverified);
+    }
+
+    #[cfg(all(feature = "gb_vat", feature = "eu_vat"))]
+    #[test]
+    fn test_parse_verification_xi_provider() {
+        let body = r#"{"target": {"name": "NI Company Ltd", "vatNumber": "591819014"}}"#;
+        let tax_id = TaxId::new("XI591819014").unwrap();
+
+        let config = VerificationConfig { xi_via_hmrc: true, ..Default::default() };
+        let verification = tax_id.parse_verification_with_config(200, body, &config).unwrap();
+        assert_eq!(verification.provider(), Provider::Hmrc);
+    }
+
+    #[cfg(feature = "gb_vat")]
+    #[test]
+    fn test_parse_response_verified_with_consultation_number() {
+        let response = VerificationResponse::new(
+            200,
+            r#"{
+                "target": {
+                    "name": "VIRGIN ATLANTIC AIRWAYS LTD",
+                    "vatNumber": "425216184"
+                },
+                "requester": "591819014",
+                "consultationNumber": "XNJ-QDR-HBT",
+                "processingDate": "2024-05-06T09:18:58+01:00"
+            }"#.to_string()
+        );
+
+        let verifier = Hmrc;
+        let verification = verifier.parse_response(response).unwrap();
+
+        assert_eq!(verification.status(), &Verified);
+        assert_eq!(verification.data(), &json!({
+            "name": "VIRGIN ATLANTIC AIRWAYS LTD",
+            "vatNumber": "425216184",
+            "consultationNumber": "XNJ-QDR-HBT"
+        }));
+    }
+
+    #[cfg(feature = "gb_vat")]
+    #[test]
+    fn test_uri() {
+        let tax_id = TaxId::new("GB425216184").unwrap();
+
+        let config = VerificationConfig::default();
+        assert_eq!(Hmrc::uri(&tax_id, &config), format!("{}/425216184", BASE_URI));
+
+        let config = VerificationConfig {
+            hmrc_requester_vrn: Some("GB591819014".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(Hmrc::uri(&tax_id, &config), format!("{}/425216184/591819014", BASE_URI));
+
+        let config = VerificationConfig {
+            endpoints: crate::Endpoints {
+                hmrc: Some("https://test-api.service.hmrc.gov.uk/organisations/vat/check-vat-number/lookup".to_string()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        assert_eq!(
+            Hmrc::uri(&tax_id, &config),
+            "https://test-api.service.hmrc.gov.uk/organisations/vat/check-vat-number/lookup/425216184"
+        );
+    }
+
+    #[cfg(feature = "gb_vat")]
+    #[test]
+    fn test_parse_response_non_json() {
+        let body = format!("<html>{}</html>", "Service Unavailable ".repeat(10));
+        let response = VerificationResponse::new(503, body.clone());
+
+        let verifier = Hmrc;
+        let error = verifier.parse_response(response).unwrap_err();
+
+        match &error {
+            VerificationError::JsonParsingError { provider, body_snippet, .. } => {
+                assert_eq!(*provider, "HMRC");
+                assert_eq!(body_snippet, &body[..100]);
+            }
+            _ => panic!("Expected JsonParsingError"),
+        }
+        assert!(error.to_string().starts_with("JSON parsing error from HMRC: "));
+        assert!(std::error::Error::source(&error).is_some());
+    }
+
+    #[cfg(feature = "gb_vat")]
+    #[test]
+    fn test_parse_response_rate_limited() {
+        let response = VerificationResponse::new(
+            429,
+            r#"{
+                "code": "MESSAGE_THROTTLED_OUT",
+                "message": "The application has reached its maximum rate limit"
+            }"#.to_string()
+        ).with_retry_after(Some("30".to_string()));
+
+        let verifier = Hmrc;
+        let verification = verifier.parse_response(response).unwrap();
+
+        assert_eq!(verification.status(), &Unavailable(RateLimit));
+        assert_eq!(verification.data(), &json!({
+            "code": "MESSAGE_THROTTLED_OUT",
+            "message": "The application has reached its maximum rate limit",
+            "retryAfter": 30
+        }));
+    }
+
+    #[cfg(feature = "gb_vat")]
+    #[test]
+    fn test_parse_response_rate_limited_without_json() {
+        let response = VerificationResponse::new(429, "Too Many Requests".to_string());
+
+        let verifier = Hmrc;
+        let verification = verifier.parse_response(response).unwrap();
+
+        assert_eq!(verification.status(), &Unavailable(RateLimit));
+        assert_eq!(verification.data(), &json!({}));
+    }
+
     #[cfg(feature = "gb_vat")]
     #[test]
     fn test_parse_response_unverified() {
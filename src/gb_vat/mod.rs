@@ -27,7 +27,19 @@ impl TaxIdType for GbVat {
         "gb_vat"
     }
 
-    fn syntax_map(&self) -> &HashMap<String, Regex> {
+    fn human_name(&self) -> &'static str {
+        "UK VAT number"
+    }
+
+    fn example(&self, tax_country_code: &str) -> Option<&'static str> {
+        (tax_country_code == "GB").then_some("GB591819014")
+    }
+
+    fn local_value_lengths(&self, tax_country_code: &str) -> Option<&'static [usize]> {
+        (tax_country_code == "GB").then_some(&[9, 12, 5])
+    }
+
+    fn syntax_map(&self) -> &'static HashMap<String, Regex> {
         &GB_VAT_PATTERN
     }
 
@@ -35,8 +47,13 @@ impl TaxIdType for GbVat {
         tax_country_code.to_string()
     }
 
-    fn verifier(&self) -> Box<dyn Verifier> {
-        Box::new(hmrc::Hmrc)
+    fn verifier(&self) -> &'static dyn Verifier {
+        &hmrc::Hmrc
+    }
+
+    /// The 12 digit form is the 9 digit number followed by a 3 digit branch identifier.
+    fn branch_suffix<'a>(&self, _tax_country_code: &str, local_value: &'a str) -> Option<&'a str> {
+        (local_value.len() == 12).then(|| &local_value[9..])
     }
 }
 
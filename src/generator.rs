@@ -0,0 +1,138 @@
+// Generates valid tax ids per country for sweeping tests, i.e. to catch a pattern or checksum
+// that drifted and no longer accepts any real number.
+//
+// The shape of each country is written down separately from its regex: `#` is a digit,
+// `@` an uppercase letter and everything else is taken literally. Random fills are tried until
+// one passes the syntax and, where the country has one, the checksum.
+
+use crate::{validate_checksum, TaxId, ValidationError};
+
+const ATTEMPTS: usize = 10_000;
+
+fn template(tax_country_code: &str) -> Option<&'static str> {
+    let template = match tax_country_code {
+        "AT" => "ATU########",
+        "BE" => "BE0#########",
+        "BG" => "BG#########",
+        "CY" => "CY0#######@",
+        "CZ" => "CZ########",
+        "DE" => "DE#########",
+        "DK" => "DK########",
+        "EE" => "EE10#######",
+        "EL" => "EL#########",
+        "ES" => "ESA########",
+        "FI" => "FI########",
+        "FR" => "FR###########",
+        "HR" => "HR###########",
+        "HU" => "HU########",
+        "IE" => "IE#######@",
+        "IT" => "IT###########",
+        "LT" => "LT#######1#",
+        "LU" => "LU########",
+        "LV" => "LV###########",
+        "MT" => "MT########",
+        "NL" => "NL#########B##",
+        "PL" => "PL##########",
+        "PT" => "PT#########",
+        "RO" => "RO1#######",
+        "SE" => "SE##########01",
+        "SI" => "SI########",
+        "SK" => "SK##########",
+        "XI" => "XI#########",
+        "GB" => "GB#########",
+        "CH" => "CHE-###.###.###",
+        "NO" => "NO#########MVA",
+        "SM" => "SM#####",
+        "RS" => "RS#########",
+        "UA" => "UA########",
+        "AE" => "AE100############",
+        _ => return None,
+    };
+
+    Some(template)
+}
+
+/// Xorshift, good enough to vary the generated digits without a dependency.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self, bound: u64) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0 % bound
+    }
+}
+
+fn fill(template: &str, rng: &mut Rng) -> String {
+    let mut value = String::with_capacity(template.len());
+    for c in template.chars() {
+        let c = match c {
+            '@' => (b'A' + rng.next(26) as u8) as char,
+            '#' => char::from_digit(rng.next(10) as u32, 10).unwrap(),
+            c => c,
+        };
+        value.push(c);
+    }
+    value
+}
+
+fn is_valid(tax_country_code: &str, value: &str) -> bool {
+    if TaxId::validate_syntax(value).is_err() {
+        return false;
+    }
+
+    let local_value = &value[tax_country_code.len()..];
+    match validate_checksum(tax_country_code, local_value) {
+        Ok(()) | Err(ValidationError::ChecksumNotAvailable(_)) => true,
+        Err(_) => false,
+    }
+}
+
+/// A syntactically valid, and where implemented checksum-valid, value for the tax country code.
+/// `None` for an unsupported country or when no valid value turned up.
+pub fn example_valid(tax_country_code: &str) -> Option<String> {
+    example_valid_with_seed(tax_country_code, 1)
+}
+
+/// Same as `example_valid`, with a different value per `seed`.
+pub fn example_valid_with_seed(tax_country_code: &str, seed: u64) -> Option<String> {
+    let template = template(tax_country_code)?;
+    let mut rng = Rng(seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1);
+
+    (0..ATTEMPTS)
+        .map(|_| fill(template, &mut rng))
+        .find(|value| is_valid(tax_country_code, value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::supported_countries;
+
+    #[test]
+    fn test_example_valid_round_trip() {
+        for tax_country_code in supported_countries() {
+            for seed in 1..=25 {
+                let value = example_valid_with_seed(tax_country_code, seed)
+                    .unwrap_or_else(|| panic!("no valid value generated for {}", tax_country_code));
+
+                let tax_id = TaxId::new(&value).unwrap_or_else(|e| panic!("{}: {}", value, e));
+                assert_eq!(tax_id.tax_country_code(), tax_country_code, "{}", value);
+                assert_eq!(tax_id.value(), value);
+            }
+        }
+    }
+
+    #[test]
+    fn test_example_valid_unsupported() {
+        assert_eq!(example_valid("XX"), None);
+    }
+
+    #[cfg(feature = "eu_vat")]
+    #[test]
+    fn test_example_valid_is_deterministic() {
+        assert_eq!(example_valid("DE"), example_valid("DE"));
+        assert_ne!(example_valid_with_seed("DE", 1), example_valid_with_seed("DE", 2));
+    }
+}
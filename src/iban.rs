@@ -0,0 +1,51 @@
+use crate::syntax::supported_countries;
+
+/// Derives the tax country code from the leading country code of an IBAN,
+/// i.e. to pre-select the VAT country when only bank details are known.
+///
+/// Returns `None` when the IBAN doesn't start with a country code and check digits,
+/// or when the country isn't supported. Greek IBANs (GR) map to the EL tax country code.
+pub fn country_from_iban(iban: &str) -> Option<&'static str> {
+    let iban = iban.trim_start();
+    let prefix = iban.get(0..4)?;
+    let (country, check_digits) = prefix.split_at(2);
+
+    if !country.chars().all(|c| c.is_ascii_uppercase()) || !check_digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+
+    let tax_country_code = match country {
+        "GR" => "EL",
+        _ => country,
+    };
+
+    supported_countries().into_iter().find(|code| *code == tax_country_code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "eu_vat")]
+    #[test]
+    fn test_country_from_iban_eu_vat() {
+        assert_eq!(country_from_iban("SE4550000000058398257466"), Some("SE"));
+        assert_eq!(country_from_iban("DE89 3704 0044 0532 0130 00"), Some("DE"));
+        assert_eq!(country_from_iban("GR1601101250000000012300695"), Some("EL"));
+    }
+
+    #[cfg(feature = "gb_vat")]
+    #[test]
+    fn test_country_from_iban_gb_vat() {
+        assert_eq!(country_from_iban("GB29NWBK60161331926819"), Some("GB"));
+    }
+
+    #[test]
+    fn test_country_from_iban_invalid() {
+        assert_eq!(country_from_iban("not an iban"), None);
+        assert_eq!(country_from_iban("se4550000000058398257466"), None);
+        assert_eq!(country_from_iban("US12"), None);
+        assert_eq!(country_from_iban("SE"), None);
+        assert_eq!(country_from_iban(""), None);
+    }
+}
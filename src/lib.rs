@@ -1,8 +1,20 @@
 #![doc = include_str!("../README.md")]
 
+mod builder;
+mod cache;
+#[cfg(feature = "csv")]
+mod csv;
 mod errors;
+#[cfg(test)]
+mod generator;
+#[cfg(feature = "iban")]
+mod iban;
 mod verification;
 mod syntax;
+#[cfg(feature = "async")]
+mod stream;
+#[cfg(any(feature = "eu_vat", feature = "rs_pib", feature = "ua_edrpou"))]
+mod checksum;
 
 #[cfg(feature = "eu_vat")]
 mod eu_vat;
@@ -20,21 +32,59 @@ use ch_vat::ChVat;
 mod no_vat;
 #[cfg(feature = "no_vat")]
 use no_vat::NoVat;
+#[cfg(feature = "no_vat")]
+pub use no_vat::untranslated_keys;
+#[cfg(feature = "sm_vat")]
+mod sm_vat;
+#[cfg(feature = "sm_vat")]
+use sm_vat::SmVat;
+#[cfg(feature = "rs_pib")]
+mod rs_pib;
+#[cfg(feature = "rs_pib")]
+use rs_pib::RsPib;
+#[cfg(feature = "ua_edrpou")]
+mod ua_edrpou;
+#[cfg(feature = "ua_edrpou")]
+use ua_edrpou::UaEdrpou;
+#[cfg(feature = "ae_trn")]
+mod ae_trn;
+#[cfg(feature = "ae_trn")]
+use ae_trn::AeTrn;
+#[cfg(any(feature = "sm_vat", feature = "rs_pib", feature = "ua_edrpou", feature = "ae_trn"))]
+mod offline;
 
 use std::collections::HashMap;
 use std::fmt;
 use regex::Regex;
 use syntax::SYNTAX;
-use verification::{Verifier};
-pub use verification::{Verification, VerificationStatus, UnavailableReason};
+use verification::{Verifier, VerificationResponse};
+pub use verification::{DATA_SCHEMA_VERSION, Endpoints, Provider, Verification, VerificationConfig, VerificationStatus, UnavailableReason};
 pub use errors::{ValidationError, VerificationError};
+pub use builder::TaxIdBuilder;
+pub use cache::{InMemoryCache, VerificationCache};
+#[cfg(not(target_arch = "wasm32"))]
+pub use cache::CachingVerifier;
+pub use syntax::{detect_country, supported_countries, syntax_patterns};
+#[cfg(feature = "iban")]
+pub use iban::country_from_iban;
+#[cfg(feature = "csv")]
+pub use csv::{validate_csv, validate_csv_with_options, CsvOptions, CsvValidationRow};
+#[cfg(feature = "async")]
+pub use stream::verify_stream;
 
 
-trait TaxIdType {
+// Send + Sync so a TaxId can move to another thread, i.e. in `verify_stream`.
+trait TaxIdType: Send + Sync {
     fn name(&self) -> &'static str;
-    fn syntax_map(&self) -> &HashMap<String, Regex>;
+    /// A human readable name of the scheme. IE: EU VAT number
+    fn human_name(&self) -> &'static str;
+    /// A valid example value for the given tax country code.
+    fn example(&self, tax_country_code: &str) -> Option<&'static str>;
+    /// The accepted lengths of the local value, the part after the tax country code.
+    fn local_value_lengths(&self, tax_country_code: &str) -> Option<&'static [usize]>;
+    fn syntax_map(&self) -> &'static HashMap<String, Regex>;
     fn validate_syntax(&self, value: &str) -> Result<(), ValidationError> {
-        let tax_country_code = &value[0..2];
+        let (tax_country_code, _) = split_tax_country_code(value)?;
         let pattern = self.syntax_map()
             .get(tax_country_code)
             .ok_or(ValidationError::UnsupportedCountryCode(tax_country_code.to_string()));
@@ -45,8 +95,232 @@ trait TaxIdType {
             Err(ValidationError::InvalidSyntax)
         }
     }
+    /// Validates the check digit(s) of an already syntax-validated value.
+    /// Types without a known checksum algorithm accept every value.
+    fn validate_checksum(&self, _value: &str) -> Result<(), ValidationError> {
+        Ok(())
+    }
+    /// Whether `validate_checksum` performs a real check for the given tax country code.
+    fn has_checksum(&self, _tax_country_code: &str) -> bool {
+        false
+    }
     fn country_code_from_tax_country(&self, tax_country_code: &str) -> String;
-    fn verifier(&self) -> Box<dyn Verifier>;
+    /// Verifiers are stateless, so a shared reference is handed out instead of boxing one per call.
+    fn verifier(&self) -> &'static dyn Verifier;
+    /// Whether the verifier queries a government database, false for stubs that always
+    /// return `Unavailable(ServiceUnavailable)`.
+    fn has_live_verifier(&self) -> bool {
+        true
+    }
+    /// Whether the verifier's service is reachable, probed by verifying the country's example.
+    /// Always false without a live verifier, as no service is queried.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn verifier_health(&self, tax_country_code: &str) -> Result<bool, VerificationError> {
+        if !self.has_live_verifier() {
+            return Ok(false);
+        }
+
+        let example = self.example(tax_country_code)
+            .ok_or(VerificationError::UnsupportedCountryCode(tax_country_code.to_string()))?;
+        let tax_id = TaxId::new(example)
+            .map_err(|_| VerificationError::UnsupportedCountryCode(tax_country_code.to_string()))?;
+        verification::probe(self.verifier(), &tax_id, &VerificationConfig::default())
+    }
+    /// The part of a syntax-validated local value that numbers a branch or group member,
+    /// for schemes that define one.
+    fn branch_suffix<'a>(&self, _tax_country_code: &str, _local_value: &'a str) -> Option<&'a str> {
+        None
+    }
+}
+
+/// Splits a value into its tax country code and local value, refusing values too short to
+/// hold a country code and at least one character of a number.
+fn split_tax_country_code(value: &str) -> Result<(&str, &str), ValidationError> {
+    match value.char_indices().nth(2) {
+        Some((index, _)) => Ok(value.split_at(index)),
+        None => Err(ValidationError::TooShort),
+    }
+}
+
+fn id_type_for(tax_country_code: &str) -> Result<Box<dyn TaxIdType>, ValidationError> {
+    let id_type: Box<dyn TaxIdType> = match tax_country_code {
+        #[cfg(feature = "gb_vat")]
+        "GB" => Box::new(GbVat),
+        #[cfg(feature = "ch_vat")]
+        "CH" => Box::new(ChVat),
+        #[cfg(feature = "no_vat")]
+        "NO" => Box::new(NoVat),
+        #[cfg(feature = "sm_vat")]
+        "SM" => Box::new(SmVat),
+        #[cfg(feature = "rs_pib")]
+        "RS" => Box::new(RsPib),
+        #[cfg(feature = "ae_trn")]
+        "AE" => Box::new(AeTrn),
+        #[cfg(feature = "ua_edrpou")]
+        "UA" => Box::new(UaEdrpou),
+        #[cfg(feature = "eu_vat")]
+        _ if eu_vat::COUNTRIES.contains(&tax_country_code) => Box::new(EuVat),
+        _ => return Err(ValidationError::UnsupportedCountryCode(tax_country_code.to_string()))
+    };
+
+    Ok(id_type)
+}
+
+/// Removes the spaces and hyphens people tend to type as separators.
+/// Swiss values are only trimmed as their pattern expects the dotted and hyphenated form
+/// and a space before the MWST/TVA/IVA suffix.
+fn normalize(value: &str) -> String {
+    let value = value.trim();
+    if value.starts_with("CH") {
+        return value.to_string();
+    }
+
+    value.chars().filter(|c| !c.is_whitespace() && *c != '-').collect()
+}
+
+/// A change `TaxId::new_with_report` applied to the input before validating it.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Normalization {
+    /// Leading or trailing whitespace was removed.
+    TrimmedWhitespace,
+    /// Lowercase letters were converted to uppercase.
+    UpperCased,
+    /// Spaces or hyphens used as separators were removed.
+    RemovedSeparators,
+}
+
+fn sanitize(value: &str) -> (String, Vec<Normalization>) {
+    let mut normalizations = Vec::new();
+
+    let trimmed = value.trim();
+    if trimmed.len() != value.len() {
+        normalizations.push(Normalization::TrimmedWhitespace);
+    }
+
+    let upper_cased = trimmed.to_uppercase();
+    if upper_cased != trimmed {
+        normalizations.push(Normalization::UpperCased);
+    }
+
+    let cleaned = normalize(&upper_cased);
+    if cleaned != upper_cased {
+        normalizations.push(Normalization::RemovedSeparators);
+    }
+
+    (cleaned, normalizations)
+}
+
+/// Whether a local value looks like a placeholder: a run of the same digit (999999999) or of
+/// consecutive digits (123456789) from the start. Up to three trailing digits are ignored,
+/// as placeholders are often padded to pass the checksum, i.e. LT999999919.
+fn is_dummy(local_value: &str) -> bool {
+    let digits: Vec<i8> = local_value.bytes()
+        .filter(u8::is_ascii_digit)
+        .map(|b| (b - b'0') as i8)
+        .collect();
+    if digits.len() < 6 {
+        return false;
+    }
+
+    let run = |step: i8| 1 + digits.windows(2)
+        .take_while(|pair| (pair[1] - pair[0]).rem_euclid(10) == step.rem_euclid(10))
+        .count();
+    let longest = [0, 1, -1].into_iter().map(run).max().unwrap_or(0);
+
+    longest >= 6 && longest + 3 >= digits.len()
+}
+
+/// Returns a valid example tax id for the given tax country code, i.e. to use as a
+/// placeholder before the user has entered a value. IE: SE -> SE556703748501
+pub fn country_example(tax_country_code: &str) -> Option<&'static str> {
+    id_type_for(tax_country_code).ok()?.example(tax_country_code)
+}
+
+/// Returns the cargo features this crate was built with, i.e. to diagnose why a country
+/// isn't supported. IE: ["eu_vat", "gb_vat"]
+pub fn enabled_features() -> Vec<&'static str> {
+    [
+        ("eu_vat", cfg!(feature = "eu_vat")),
+        ("gb_vat", cfg!(feature = "gb_vat")),
+        ("ch_vat", cfg!(feature = "ch_vat")),
+        ("no_vat", cfg!(feature = "no_vat")),
+        ("sm_vat", cfg!(feature = "sm_vat")),
+        ("rs_pib", cfg!(feature = "rs_pib")),
+        ("ua_edrpou", cfg!(feature = "ua_edrpou")),
+        ("ae_trn", cfg!(feature = "ae_trn")),
+        ("tracing", cfg!(feature = "tracing")),
+        ("iban", cfg!(feature = "iban")),
+        ("csv", cfg!(feature = "csv")),
+        ("async", cfg!(feature = "async")),
+    ]
+    .into_iter()
+    .filter_map(|(feature, enabled)| enabled.then_some(feature))
+    .collect()
+}
+
+/// Returns the tax country codes of the enabled countries that can be verified against a
+/// government database, sorted alphabetically. Countries with syntax support only, like SM,
+/// are left out. IE: ["AT", "BE", ..., "XI"]
+pub fn live_verification_countries() -> Vec<&'static str> {
+    supported_countries()
+        .into_iter()
+        .filter(|code| id_type_for(code).is_ok_and(|id_type| id_type.has_live_verifier()))
+        .collect()
+}
+
+/// Checks whether the government service verifying the tax country code is reachable, i.e. before
+/// a batch run. A known tax id is verified and only reachability is reported, not its validity.
+/// Countries without a live verifier, like SM, report false without a request.
+///
+/// Returns `VerificationError::UnsupportedCountryCode` for countries that aren't enabled.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn service_health(tax_country_code: &str) -> Result<bool, VerificationError> {
+    id_type_for(tax_country_code)
+        .map_err(|_| VerificationError::UnsupportedCountryCode(tax_country_code.to_string()))?
+        .verifier_health(tax_country_code)
+}
+
+/// Validates only the check digits of a local value, for callers that already split the
+/// tax country code from the number. IE: ("DE", "136695976")
+///
+/// Returns `ValidationError::ChecksumNotAvailable` for countries validated by syntax only,
+/// and `ValidationError::InvalidSyntax` when the value doesn't fit the country's pattern.
+pub fn validate_checksum(tax_country_code: &str, local_value: &str) -> Result<(), ValidationError> {
+    let id_type = id_type_for(tax_country_code)?;
+    if !id_type.has_checksum(tax_country_code) {
+        return Err(ValidationError::ChecksumNotAvailable(tax_country_code.to_string()));
+    }
+
+    let value = format!("{}{}", tax_country_code, local_value);
+    id_type.validate_syntax(&value)?;
+    id_type.validate_checksum(&value)
+}
+
+/// Returns the accepted lengths of the local value for the given tax country code, i.e. to
+/// show typing progress. IE: GB -> [9, 12, 5], CZ -> [8, 9, 10]
+pub fn local_value_lengths(tax_country_code: &str) -> Option<Vec<usize>> {
+    let lengths = id_type_for(tax_country_code).ok()?.local_value_lengths(tax_country_code)?;
+    Some(lengths.to_vec())
+}
+
+/// How thoroughly a TaxId could be validated without contacting any government database.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum LocalConfidence {
+    /// Only the syntax was validated, no checksum algorithm is implemented for the country.
+    SyntaxOnly,
+    /// Both the syntax and the check digit(s) were validated.
+    SyntaxAndChecksum,
+}
+
+/// A plain data representation of a TaxId, see `TaxId::into_parts`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaxIdParts {
+    pub value: String,
+    pub country_code: String,
+    pub tax_country_code: String,
+    pub local_value: String,
+    /// The type of tax id in snake_case, as returned by `TaxId::tax_id_type`.
+    pub id_type_name: String,
 }
 
 pub struct TaxId {
@@ -55,6 +329,7 @@ pub struct TaxId {
     tax_country_code: String,
     local_value: String,
     id_type: Box<dyn TaxIdType>,
+    checksum_validated: bool,
 }
 
 impl fmt::Debug for TaxId {
@@ -68,7 +343,7 @@ impl TaxId {
     /// Use this associated function to validate the syntax of a given tax id number against
     /// its country-specific regex pattern without creating any TaxId.
     pub fn validate_syntax(value: &str) -> Result<(), ValidationError> {
-        let tax_country_code = &value[0..2];
+        let (tax_country_code, _) = split_tax_country_code(value)?;
         SYNTAX.get(tax_country_code)
             .ok_or(ValidationError::UnsupportedCountryCode(tax_country_code.to_string()))
             .and_then(|syntax| {
@@ -80,43 +355,179 @@ impl TaxId {
             })
     }
 
-    /// Constructs a TaxId after validating its syntax based on the country-specific regex pattern.
-    /// If the syntax validation is successful, the returned TaxId can be used for further
+    /// Validates the syntax of many values at once, i.e. the rows of a CSV import.
+    /// Every value gets a result, in the same order as given, instead of stopping at the first error.
+    pub fn validate_syntax_many<I, S>(values: I) -> Vec<(String, Result<(), ValidationError>)>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        values.into_iter()
+            .map(|value| {
+                let value = value.as_ref();
+                (value.to_string(), TaxId::validate_syntax(value))
+            })
+            .collect()
+    }
+
+    /// Validates the syntax like `validate_syntax` and returns the type of tax id it matched
+    /// in snake_case, without constructing a TaxId. IE: SE556703748501 -> eu_vat
+    pub fn classify(value: &str) -> Result<&'static str, ValidationError> {
+        let id_type = id_type_for(split_tax_country_code(value)?.0)?;
+        id_type.validate_syntax(value)?;
+        Ok(id_type.name())
+    }
+
+    /// Validates the syntax of a local value against an explicitly given tax country code,
+    /// for forms where the country and the number are separate inputs.
+    /// IE: ("SE", "556703748501") is validated as SE556703748501.
+    pub fn validate_syntax_for(tax_country_code: &str, local_value: &str) -> Result<(), ValidationError> {
+        TaxId::validate_syntax(&format!("{}{}", tax_country_code, local_value))
+    }
+
+    /// Constructs a TaxId after validating its syntax based on the country-specific regex pattern
+    /// and, where the country has one, its checksum.
+    /// If the validation is successful, the returned TaxId can be used for further
     /// verification against the corresponding government database.
+    ///
+    /// Spaces and hyphens used as separators are removed first, i.e. `SE 5567 0374 8501`
+    /// becomes `SE556703748501`.
     pub fn new(value: &str) -> Result<TaxId, ValidationError> {
-        let tax_country_code = &value[0..2];
-        let local_value = &value[2..];
-
-        let id_type: Box<dyn TaxIdType> = match tax_country_code {
-            #[cfg(feature = "gb_vat")]
-            "GB" => Box::new(GbVat),
-            #[cfg(feature = "ch_vat")]
-            "CH" => Box::new(ChVat),
-            #[cfg(feature = "no_vat")]
-            "NO" => Box::new(NoVat),
-            #[cfg(feature = "eu_vat")]
-            _ if eu_vat::COUNTRIES.contains(&tax_country_code) => Box::new(EuVat),
-            _ => return Err(ValidationError::UnsupportedCountryCode(tax_country_code.to_string()))
-        };
+        TaxId::parse(value, true)
+    }
+
+    /// Same as `new`, but skips the checksum validation, i.e. to import legacy numbers that are
+    /// registered but fail today's check digit rules. The `local_confidence` is then `SyntaxOnly`.
+    pub fn new_syntax_only(value: &str) -> Result<TaxId, ValidationError> {
+        TaxId::parse(value, false)
+    }
+
+    fn parse(value: &str, validate_checksum: bool) -> Result<TaxId, ValidationError> {
+        let value = &normalize(value);
+        let (tax_country_code, local_value) = split_tax_country_code(value)?;
+
+        let id_type = id_type_for(tax_country_code)?;
+        // Tax ids are ASCII only, which keeps byte based slicing of the value on char boundaries
+        if !value.is_ascii() {
+            return Err(ValidationError::InvalidSyntax);
+        }
 
         id_type.validate_syntax(value)?;
+        if validate_checksum {
+            id_type.validate_checksum(value)?;
+        }
 
         Ok(TaxId {
             country_code: id_type.country_code_from_tax_country(tax_country_code),
             value: value.to_string(),
             tax_country_code: tax_country_code.to_string(),
             local_value: local_value.to_string(),
+            checksum_validated: validate_checksum && id_type.has_checksum(tax_country_code),
             id_type,
         })
     }
 
+    /// Starts a `TaxIdBuilder`, for forms where the country and the number are separate inputs.
+    /// IE: `TaxId::builder().country("SE").number("556703748501").build()`
+    pub fn builder() -> TaxIdBuilder {
+        TaxIdBuilder::default()
+    }
+
+    /// Same as `new`, but also trims and upper-cases the input and reports every change
+    /// made to it, i.e. to tell the user that their entry was cleaned up.
+    pub fn new_with_report(value: &str) -> Result<(TaxId, Vec<Normalization>), ValidationError> {
+        let (value, normalizations) = sanitize(value);
+        Ok((TaxId::new(&value)?, normalizations))
+    }
+
+    /// Same as `new`, but only accepts tax ids whose checksum can be validated.
+    /// Returns `ValidationError::ChecksumNotAvailable` for countries that are validated by syntax only.
+    pub fn new_strict(value: &str) -> Result<TaxId, ValidationError> {
+        let tax_id = TaxId::new(value)?;
+        match tax_id.local_confidence() {
+            LocalConfidence::SyntaxAndChecksum => Ok(tax_id),
+            LocalConfidence::SyntaxOnly => Err(ValidationError::ChecksumNotAvailable(tax_id.tax_country_code)),
+        }
+    }
+
+    /// Same as `new`, but refuses obvious placeholders with `ValidationError::SuspectedDummy`,
+    /// i.e. to catch users pasting an example into a form. See `is_suspected_dummy`.
+    pub fn new_rejecting_dummies(value: &str) -> Result<TaxId, ValidationError> {
+        let tax_id = TaxId::new(value)?;
+        if tax_id.is_suspected_dummy() {
+            return Err(ValidationError::SuspectedDummy);
+        }
+        Ok(tax_id)
+    }
+
+    /// Same as `new`, but refuses tax ids whose `country_code` is not in `allowed`
+    /// with `ValidationError::CountryNotAllowed`.
+    pub fn new_restricted(value: &str, allowed: &[&str]) -> Result<TaxId, ValidationError> {
+        let tax_id = TaxId::new(value)?;
+        if !allowed.contains(&tax_id.country_code()) {
+            return Err(ValidationError::CountryNotAllowed(tax_id.country_code().to_string()));
+        }
+        Ok(tax_id)
+    }
+
     /// Performs a request to verify the tax id against the corresponding government database.
+    #[cfg(not(target_arch = "wasm32"))]
     pub fn verify(&self) -> Result<Verification, VerificationError> {
-        self.id_type().verifier().verify(self)
+        self.verify_with_config(&VerificationConfig::default())
+    }
+
+    /// Same as `verify`, but lets the caller adjust the verification through a `VerificationConfig`.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn verify_with_config(&self, config: &VerificationConfig) -> Result<Verification, VerificationError> {
+        self.verifier_for(config).verify(self, config)
+    }
+
+    /// Re-verifies the tax id only when the `previous` verification was performed more than
+    /// `max_age` ago, otherwise a copy of `previous` is returned without any request.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn reverify_if_stale(&self, previous: &Verification, max_age: std::time::Duration) -> Result<Verification, VerificationError> {
+        self.reverify_if_stale_with(previous, max_age, TaxId::verify)
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn reverify_if_stale_with<F>(&self, previous: &Verification, max_age: std::time::Duration, verify: F) -> Result<Verification, VerificationError>
+    where
+        F: FnOnce(&TaxId) -> Result<Verification, VerificationError>,
+    {
+        if previous.is_older_than(max_age) {
+            verify(self)
+        } else {
+            Ok(previous.clone())
+        }
+    }
+
+    /// Performs a request to verify the tax id against the corresponding government database.
+    ///
+    /// Only available on wasm targets, where requests go through the browser's fetch API.
+    #[cfg(target_arch = "wasm32")]
+    pub async fn verify_async(&self) -> Result<Verification, VerificationError> {
+        self.verify_async_with_config(&VerificationConfig::default()).await
+    }
+
+    /// Same as `verify_async`, but lets the caller adjust the verification through a `VerificationConfig`.
+    #[cfg(target_arch = "wasm32")]
+    pub async fn verify_async_with_config(&self, config: &VerificationConfig) -> Result<Verification, VerificationError> {
+        verification::verify_async(self.verifier_for(config), self, config).await
     }
 
     /// Returns the full tax id value. IE: SE556703748501
     pub fn value(&self) -> &str { &self.value }
+    /// Returns the value prefixed with the ISO country code instead of the tax country code
+    /// where they differ, for systems expecting ISO codes. IE: EL094259216 -> GR094259216,
+    /// XI591819014 -> GB591819014
+    ///
+    /// The result isn't necessarily a valid tax id anymore, i.e. VIES only knows EL and XI.
+    pub fn to_iso_value(&self) -> String {
+        if self.country_code == self.tax_country_code {
+            return self.value.clone();
+        }
+        format!("{}{}", self.country_code, self.local_value)
+    }
     /// Returns the country code. IE: SE
     pub fn country_code(&self) -> &str { &self.country_code }
     /// Returns the tax country code. IE: SE
@@ -128,12 +539,132 @@ impl TaxId {
     ///
     /// EL is the tax country code for Greece.
     pub fn tax_country_code(&self) -> &str { &self.tax_country_code }
+    /// Whether the tax id belongs to an EU VAT scheme, including XI for Northern Ireland.
+    /// IE: true for SE and XI, false for GB, CH and NO.
+    pub fn is_eu(&self) -> bool {
+        #[cfg(feature = "eu_vat")]
+        { eu_vat::COUNTRIES.contains(&self.tax_country_code()) }
+        #[cfg(not(feature = "eu_vat"))]
+        { false }
+    }
+    /// Whether the tax id is a Northern Ireland (XI) VAT number.
+    pub fn is_northern_ireland(&self) -> bool { self.tax_country_code == "XI" }
     /// Returns the local value of the tax id. IE: 556703748501
     pub fn local_value(&self) -> &str { &self.local_value }
+    /// Returns only the digits of the local value, without letters and separators.
+    /// IE: ATU13585627 -> 13585627, CHE-116.281.710 -> 116281710, NO974760673MVA -> 974760673
+    pub fn local_digits(&self) -> String {
+        self.local_value.chars().filter(|c| c.is_ascii_digit()).collect()
+    }
+
+    /// Returns the value with everything but the tax country code and the last two characters
+    /// replaced by asterisks, i.e. for logging. IE: SE**********01
+    ///
+    /// Values too short to keep two characters still have at least their middle masked.
+    pub fn masked(&self) -> String {
+        let (prefix, rest) = self.value.split_at(2);
+        let len = rest.chars().count();
+        let kept = if len > 2 { 2 } else { len.saturating_sub(1) };
+        let suffix: String = rest.chars().skip(len - kept).collect();
+        format!("{}{}{}", prefix, "*".repeat(len - kept), suffix)
+    }
+
+    /// Whether the local value looks like a placeholder rather than a real number: the same
+    /// digit or consecutive digits, ignoring up to three trailing (check) digits.
+    /// IE: true for RO99999999, LT999999919 and SE123456789101.
+    pub fn is_suspected_dummy(&self) -> bool { is_dummy(&self.local_value) }
+
+    /// Whether verifying the tax id queries a government database. When false, `verify`
+    /// always returns `Unavailable(ServiceUnavailable)` without a request.
+    pub fn has_live_verifier(&self) -> bool { self.id_type.has_live_verifier() }
+
+    /// Returns the part of the local value numbering a branch or group member, for schemes
+    /// that define one, i.e. to tell a head office from its branches.
+    /// IE: NL004495445B01 -> B01, SE556703748501 -> 01, GB591819014001 -> 001
+    pub fn branch_suffix(&self) -> Option<&str> {
+        self.id_type.branch_suffix(&self.tax_country_code, &self.local_value)
+    }
+
+    /// Returns how thoroughly the tax id was validated locally, without verification.
+    pub fn local_confidence(&self) -> LocalConfidence {
+        if self.checksum_validated {
+            LocalConfidence::SyntaxAndChecksum
+        } else {
+            LocalConfidence::SyntaxOnly
+        }
+    }
+
+    /// Returns a human readable name of the tax id type. IE: EU VAT number
+    pub fn human_name(&self) -> &str { self.id_type.human_name() }
+    /// Returns a valid example value for the same tax country code. IE: SE556703748501
+    pub fn example(&self) -> &str {
+        self.id_type.example(&self.tax_country_code).unwrap_or_default()
+    }
+
+    /// Splits the TaxId into its owned parts, i.e. for storage or pattern matching.
+    pub fn into_parts(self) -> TaxIdParts {
+        TaxIdParts {
+            id_type_name: self.id_type.name().to_string(),
+            value: self.value,
+            country_code: self.country_code,
+            tax_country_code: self.tax_country_code,
+            local_value: self.local_value,
+        }
+    }
+
+    /// Parses a government response obtained elsewhere, i.e. through a proxy of your own,
+    /// the same way `verify` parses the response to its own request.
+    pub fn parse_verification(&self, status: u16, body: &str) -> Result<Verification, VerificationError> {
+        self.parse_verification_with_config(status, body, &VerificationConfig::default())
+    }
+
+    /// Same as `parse_verification`, taking the routing and parsing options of a
+    /// `VerificationConfig` into account.
+    pub fn parse_verification_with_config(&self, status: u16, body: &str, config: &VerificationConfig) -> Result<Verification, VerificationError> {
+        let response = VerificationResponse::new(status, body.to_string());
+        verification::into_verification(self.verifier_for(config), self, response, config)
+    }
+
+    /// Returns the URL a verification of the tax id is sent to, i.e. to log or allowlist
+    /// outbound destinations. IE: https://api.service.hmrc.gov.uk/organisations/vat/check-vat-number/lookup/591819014
+    pub fn verification_endpoint(&self) -> String {
+        self.verification_endpoint_with_config(&VerificationConfig::default())
+    }
+
+    /// Same as `verification_endpoint`, taking the endpoint overrides and routing of a
+    /// `VerificationConfig` into account.
+    pub fn verification_endpoint_with_config(&self, config: &VerificationConfig) -> String {
+        self.verifier_for(config).endpoint(self, config)
+    }
 
     /// Returns the type of tax id in snake_case. IE: eu_vat, gb_vat, ch_va or no_vat
     pub fn tax_id_type(&self) -> &str { self.id_type.name() }
-    fn id_type(&self) -> &Box<dyn TaxIdType> { &self.id_type }
+    fn verifier(&self) -> &'static dyn Verifier { self.id_type.verifier() }
+    fn verifier_for(&self, config: &VerificationConfig) -> &'static dyn Verifier {
+        #[cfg(feature = "gb_vat")]
+        if config.xi_via_hmrc && self.is_northern_ireland() {
+            return GbVat.verifier();
+        }
+        #[cfg(feature = "ch_vat")]
+        if config.ch_via_rest && self.tax_country_code == "CH" {
+            return ChVat.rest_verifier();
+        }
+        #[cfg(not(any(feature = "gb_vat", feature = "ch_vat")))]
+        let _ = config;
+
+        self.verifier()
+    }
+}
+
+impl TryFrom<&[u8]> for TaxId {
+    type Error = ValidationError;
+
+    /// Constructs a TaxId from raw bytes, i.e. a CSV field or a database column.
+    /// The bytes must be valid UTF-8, no lossy conversion is performed.
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        let value = std::str::from_utf8(value).map_err(|_| ValidationError::InvalidEncoding)?;
+        TaxId::new(value)
+    }
 }
 
 #[cfg(test)]
@@ -155,6 +686,14 @@ mod tests {
         valid_vat_numbers.push("CHE123456789");
         #[cfg(feature = "no_vat")]
         valid_vat_numbers.push("NO123456789MVA");
+        #[cfg(feature = "sm_vat")]
+        valid_vat_numbers.push("SM24165");
+        #[cfg(feature = "rs_pib")]
+        valid_vat_numbers.push("RS101134702");
+        #[cfg(feature = "ua_edrpou")]
+        valid_vat_numbers.push("UA32855961");
+        #[cfg(feature = "ae_trn")]
+        valid_vat_numbers.push("AE100235617900003");
 
         for vat_number in valid_vat_numbers {
             let valid_syntax = TaxId::validate_syntax(vat_number);
@@ -167,6 +706,34 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_too_short() {
+        for value in ["", "S", "SE", "É"] {
+            assert_eq!(TaxId::validate_syntax(value), Err(ValidationError::TooShort), "{:?}", value);
+            assert_eq!(TaxId::classify(value), Err(ValidationError::TooShort), "{:?}", value);
+            assert_eq!(TaxId::new(value).unwrap_err(), ValidationError::TooShort, "{:?}", value);
+        }
+        assert_eq!(TaxId::validate_syntax_for("SE", ""), Err(ValidationError::TooShort));
+        assert_eq!(TaxId::new(" SE ").unwrap_err(), ValidationError::TooShort);
+    }
+
+    #[test]
+    fn test_multibyte_country_code() {
+        assert_eq!(TaxId::validate_syntax("É123"), Err(ValidationError::UnsupportedCountryCode("É1".to_string())));
+        assert_eq!(TaxId::new("ÅÄ123").unwrap_err(), ValidationError::UnsupportedCountryCode("ÅÄ".to_string()));
+    }
+
+    #[cfg(feature = "eu_vat")]
+    #[test]
+    fn test_non_ascii_local_value() {
+        for value in ["IE1234567Ä", "IE1234567ä", "IE12345É7T", "SE55670374850É"] {
+            assert_eq!(TaxId::validate_syntax(value), Err(ValidationError::InvalidSyntax), "{:?}", value);
+            assert_eq!(TaxId::new(value).unwrap_err(), ValidationError::InvalidSyntax, "{:?}", value);
+        }
+        assert_eq!(TaxId::new_with_report("ie1234567ä").unwrap_err(), ValidationError::InvalidSyntax);
+        assert_eq!(TaxId::builder().country("IE").number("1234567Ä").build().unwrap_err(), ValidationError::InvalidSyntax);
+    }
+
     #[test]
     fn test_validate_syntax_unsupported_country() {
         let validation = TaxId::validate_syntax("XX123456789");
@@ -222,6 +789,14 @@ mod tests {
         assert_eq!(tax_id.unwrap_err(), ValidationError::InvalidSyntax);
     }
 
+    #[cfg(feature = "eu_vat")]
+    #[test]
+    fn test_new_eu_invalid_checksum_err() {
+        let tax_id = TaxId::new("RO18547291");
+        assert!(tax_id.is_err());
+        assert_eq!(tax_id.unwrap_err(), ValidationError::InvalidChecksum);
+    }
+
     #[cfg(feature = "gb_vat")]
     #[test]
     fn test_new_gb_unsupported_country_code_err() {
@@ -256,13 +831,19 @@ mod tests {
         assert_eq!(tax_id.tax_id_type(), "eu_vat");
     }
 
+    #[cfg(feature = "eu_vat")]
+    #[test]
+    fn test_new_gr_vat_invalid_checksum() {
+        assert_eq!(TaxId::new("EL094259217").unwrap_err(), ValidationError::InvalidChecksum);
+    }
+
     #[cfg(feature = "eu_vat")]
     #[test]
     fn test_new_gr_vat() {
-        let tax_id = TaxId::new("EL123456789").unwrap();
-        assert_eq!(tax_id.value(), "EL123456789");
+        let tax_id = TaxId::new("EL094259216").unwrap();
+        assert_eq!(tax_id.value(), "EL094259216");
         assert_eq!(tax_id.country_code(), "GR");
-        assert_eq!(tax_id.local_value(), "123456789");
+        assert_eq!(tax_id.local_value(), "094259216");
         assert_eq!(tax_id.tax_id_type(), "eu_vat");
     }
 
@@ -305,5 +886,716 @@ mod tests {
         assert_eq!(tax_id.local_value(), "123456789MVA");
         assert_eq!(tax_id.tax_id_type(), "no_vat");
     }
-}
 
+    #[cfg(feature = "eu_vat")]
+    #[test]
+    fn test_local_confidence_eu_vat() {
+        let with_checksum = TaxId::new("RO18547290").unwrap();
+        assert_eq!(with_checksum.local_confidence(), LocalConfidence::SyntaxAndChecksum);
+
+        let syntax_only = TaxId::new("SE123456789101").unwrap();
+        assert_eq!(syntax_only.local_confidence(), LocalConfidence::SyntaxOnly);
+    }
+
+    #[cfg(feature = "eu_vat")]
+    #[test]
+    fn test_new_syntax_only() {
+        assert_eq!(TaxId::new("DE136695977").unwrap_err(), ValidationError::InvalidChecksum);
+
+        let tax_id = TaxId::new_syntax_only("DE136695977").unwrap();
+        assert_eq!(tax_id.value(), "DE136695977");
+        assert_eq!(tax_id.local_confidence(), LocalConfidence::SyntaxOnly);
+
+        let tax_id = TaxId::new_syntax_only("DE136695976").unwrap();
+        assert_eq!(tax_id.local_confidence(), LocalConfidence::SyntaxOnly);
+
+        assert_eq!(TaxId::new_syntax_only("DE13669597").unwrap_err(), ValidationError::InvalidSyntax);
+    }
+
+    #[cfg(feature = "gb_vat")]
+    #[test]
+    fn test_local_confidence_gb_vat() {
+        let tax_id = TaxId::new("GB591819014").unwrap();
+        assert_eq!(tax_id.local_confidence(), LocalConfidence::SyntaxOnly);
+    }
+
+    #[cfg(feature = "eu_vat")]
+    #[test]
+    fn test_try_from_bytes() {
+        let tax_id = TaxId::try_from("SE123456789101".as_bytes()).unwrap();
+        assert_eq!(tax_id.value(), "SE123456789101");
+        assert_eq!(tax_id.country_code(), "SE");
+    }
+
+    #[cfg(feature = "eu_vat")]
+    #[test]
+    fn test_try_from_bytes_invalid_syntax_err() {
+        let result = TaxId::try_from("SE12345678910".as_bytes());
+        assert_eq!(result.unwrap_err(), ValidationError::InvalidSyntax);
+    }
+
+    #[test]
+    fn test_try_from_bytes_invalid_encoding_err() {
+        let result = TaxId::try_from(&b"SE12345678\xff101"[..]);
+        assert_eq!(result.unwrap_err(), ValidationError::InvalidEncoding);
+    }
+
+    #[test]
+    fn test_country_examples() {
+        for tax_country_code in SYNTAX.keys() {
+            let example = country_example(tax_country_code).unwrap();
+            assert!(!example.is_empty());
+            assert_eq!(TaxId::validate_syntax(example), Ok(()), "{}", example);
+            assert!(TaxId::new(example).is_ok(), "{}", example);
+        }
+    }
+
+    #[test]
+    fn test_country_example_unsupported_country() {
+        assert_eq!(country_example("US"), None);
+    }
+
+    #[cfg(feature = "eu_vat")]
+    #[test]
+    fn test_human_name_and_example() {
+        let tax_id = TaxId::new("SE123456789101").unwrap();
+        assert_eq!(tax_id.human_name(), "EU VAT number");
+        assert_eq!(tax_id.example(), "SE556703748501");
+    }
+
+    #[cfg(feature = "eu_vat")]
+    #[test]
+    fn test_reverify_if_stale_fresh() {
+        let tax_id = TaxId::new("SE123456789101").unwrap();
+        let previous = Verification::new(VerificationStatus::Verified, serde_json::json!({}));
+
+        let verification = tax_id.reverify_if_stale_with(
+            &previous,
+            std::time::Duration::from_secs(3600),
+            |_| panic!("A fresh verification should not be re-verified"),
+        ).unwrap();
+        assert_eq!(verification, previous);
+    }
+
+    #[cfg(feature = "eu_vat")]
+    #[test]
+    fn test_reverify_if_stale_stale() {
+        let tax_id = TaxId::new("SE123456789101").unwrap();
+        let previous = Verification::new(VerificationStatus::Verified, serde_json::json!({}));
+        std::thread::sleep(std::time::Duration::from_millis(2));
+
+        let verification = tax_id.reverify_if_stale_with(
+            &previous,
+            std::time::Duration::from_millis(1),
+            |_| Ok(Verification::new(VerificationStatus::Unverified, serde_json::json!({}))),
+        ).unwrap();
+        assert_eq!(verification.status(), &VerificationStatus::Unverified);
+    }
+
+    #[cfg(feature = "eu_vat")]
+    #[test]
+    fn test_is_eu_and_is_northern_ireland_eu_vat() {
+        let se = TaxId::new("SE123456789101").unwrap();
+        assert!(se.is_eu());
+        assert!(!se.is_northern_ireland());
+
+        let xi = TaxId::new("XI591819014").unwrap();
+        assert!(xi.is_eu());
+        assert!(xi.is_northern_ireland());
+    }
+
+    #[cfg(feature = "gb_vat")]
+    #[test]
+    fn test_is_eu_and_is_northern_ireland_gb_vat() {
+        let gb = TaxId::new("GB591819014").unwrap();
+        assert!(!gb.is_eu());
+        assert!(!gb.is_northern_ireland());
+    }
+
+    #[cfg(feature = "ch_vat")]
+    #[test]
+    fn test_is_eu_and_is_northern_ireland_ch_vat() {
+        let ch = TaxId::new("CHE123456789").unwrap();
+        assert!(!ch.is_eu());
+        assert!(!ch.is_northern_ireland());
+    }
+
+    #[cfg(feature = "eu_vat")]
+    #[test]
+    fn test_new_strict_with_checksum() {
+        let tax_id = TaxId::new_strict("RO18547290").unwrap();
+        assert_eq!(tax_id.value(), "RO18547290");
+        assert_eq!(TaxId::new_strict("RO18547291").unwrap_err(), ValidationError::InvalidChecksum);
+    }
+
+    #[cfg(feature = "eu_vat")]
+    #[test]
+    fn test_new_strict_syntax_only_err() {
+        let result = TaxId::new_strict("SE123456789101");
+        assert_eq!(result.unwrap_err(), ValidationError::ChecksumNotAvailable("SE".to_string()));
+    }
+
+    #[cfg(feature = "gb_vat")]
+    #[test]
+    fn test_new_strict_gb_syntax_only_err() {
+        let result = TaxId::new_strict("GB591819014");
+        assert_eq!(result.unwrap_err(), ValidationError::ChecksumNotAvailable("GB".to_string()));
+    }
+
+    #[test]
+    fn test_verifier_is_reused() {
+        // Verifiers are handed out as `&'static dyn Verifier`, so no verifier is built per
+        // verification. Being zero-sized, they carry no state between verifications either.
+        for tax_country_code in SYNTAX.keys() {
+            let tax_id = TaxId::new(country_example(tax_country_code).unwrap()).unwrap();
+            let verifier: &'static dyn Verifier = tax_id.verifier();
+            assert_eq!(std::mem::size_of_val(verifier), 0, "{}", tax_country_code);
+        }
+    }
+
+    #[cfg(feature = "eu_vat")]
+    #[test]
+    fn test_new_eu_vat_with_separators() {
+        let cases = [
+            ("SE 123456789101", "SE123456789101"),
+            ("SE-1234-5678-9101", "SE123456789101"),
+            (" ATU 1358 5627 ", "ATU13585627"),
+            ("NL 004495445 B01", "NL004495445B01"),
+        ];
+
+        for (messy, clean) in cases {
+            let tax_id = TaxId::new(messy).unwrap();
+            assert_eq!(tax_id.value(), clean);
+        }
+    }
+
+    #[cfg(feature = "gb_vat")]
+    #[test]
+    fn test_new_gb_vat_with_separators() {
+        let tax_id = TaxId::new("GB-123 4567 89").unwrap();
+        assert_eq!(tax_id.value(), "GB123456789");
+        assert_eq!(tax_id.local_value(), "123456789");
+    }
+
+    #[cfg(feature = "ch_vat")]
+    #[test]
+    fn test_new_ch_vat_keeps_separators() {
+        let tax_id = TaxId::new("CHE-123.456.789 MWST").unwrap();
+        assert_eq!(tax_id.value(), "CHE-123.456.789 MWST");
+
+        let tax_id = TaxId::new("CHE123456789").unwrap();
+        assert_eq!(tax_id.value(), "CHE123456789");
+    }
+
+    #[cfg(feature = "ch_vat")]
+    #[test]
+    fn test_new_ch_vat_surrounded_by_whitespace() {
+        let cases = [
+            (" CHE-116.281.710", "CHE-116.281.710"),
+            ("CHE-116.281.710 ", "CHE-116.281.710"),
+            ("\t CHE-116.281.710 MWST \n", "CHE-116.281.710 MWST"),
+        ];
+
+        for (padded, clean) in cases {
+            let tax_id = TaxId::new(padded).unwrap();
+            assert_eq!(tax_id.value(), clean);
+        }
+    }
+
+    #[cfg(all(feature = "eu_vat", feature = "gb_vat"))]
+    #[test]
+    fn test_verifier_for_xi_via_hmrc() {
+        let tax_id = TaxId::new("XI591819014").unwrap();
+        let hmrc_response = || verification::VerificationResponse::new(
+            200,
+            r#"{
+                "target": {
+                    "name": "NORTHERN IRELAND TRADER LTD",
+                    "vatNumber": "591819014",
+                    "address": { "line1": "1 MAIN STREET", "postcode": "BT1 1AA", "countryCode": "XI" }
+                },
+                "processingDate": "2024-05-06T09:18:58+01:00"
+            }"#.to_string()
+        );
+
+        let config = VerificationConfig { xi_via_hmrc: true, ..Default::default() };
+        let verification = tax_id.verifier_for(&config).parse_response(hmrc_response()).unwrap();
+        assert_eq!(verification.status(), &VerificationStatus::Verified);
+        assert_eq!(verification.data().get("vatNumber").unwrap(), "591819014");
+
+        // VIES expects XML, so the HMRC response doesn't parse
+        let default = tax_id.verifier_for(&VerificationConfig::default()).parse_response(hmrc_response());
+        assert!(matches!(default, Err(VerificationError::XmlParsingError { .. })));
+    }
+
+    #[cfg(all(feature = "eu_vat", feature = "gb_vat"))]
+    #[test]
+    fn test_verifier_for_only_routes_xi() {
+        let tax_id = TaxId::new("SE123456789101").unwrap();
+        let config = VerificationConfig { xi_via_hmrc: true, ..Default::default() };
+        let response = verification::VerificationResponse::new(200, "{}".to_string());
+
+        let verification = tax_id.verifier_for(&config).parse_response(response);
+        assert!(matches!(verification, Err(VerificationError::XmlParsingError { .. })));
+    }
+
+    #[cfg(feature = "eu_vat")]
+    #[test]
+    fn test_validate_syntax_for() {
+        assert_eq!(TaxId::validate_syntax_for("SE", "123456789101"), Ok(()));
+        assert_eq!(TaxId::validate_syntax_for("AT", "U13585627"), Ok(()));
+        // A Norwegian number given as Swedish
+        assert_eq!(TaxId::validate_syntax_for("SE", "974760673"), Err(ValidationError::InvalidSyntax));
+        assert_eq!(
+            TaxId::validate_syntax_for("US", "123456789"),
+            Err(ValidationError::UnsupportedCountryCode("US".to_string()))
+        );
+    }
+
+    #[cfg(feature = "eu_vat")]
+    #[test]
+    fn test_verification_endpoint_vies() {
+        let tax_id = TaxId::new("SE556703748501").unwrap();
+        assert_eq!(tax_id.verification_endpoint(), "http://ec.europa.eu/taxation_customs/vies/services/checkVatService");
+    }
+
+    #[cfg(feature = "gb_vat")]
+    #[test]
+    fn test_verification_endpoint_hmrc() {
+        let tax_id = TaxId::new("GB591819014").unwrap();
+        assert_eq!(
+            tax_id.verification_endpoint(),
+            "https://api.service.hmrc.gov.uk/organisations/vat/check-vat-number/lookup/591819014"
+        );
+    }
+
+    #[cfg(all(feature = "eu_vat", feature = "gb_vat"))]
+    #[test]
+    fn test_verification_endpoint_xi_via_hmrc() {
+        let tax_id = TaxId::new("XI591819014").unwrap();
+        let config = VerificationConfig { xi_via_hmrc: true, ..Default::default() };
+        assert_eq!(
+            tax_id.verification_endpoint_with_config(&config),
+            "https://api.service.hmrc.gov.uk/organisations/vat/check-vat-number/lookup/591819014"
+        );
+    }
+
+    #[cfg(feature = "ch_vat")]
+    #[test]
+    fn test_verification_endpoint_bfs() {
+        let tax_id = TaxId::new("CHE-116.281.710").unwrap();
+        assert_eq!(tax_id.verification_endpoint(), "https://www.uid-wse-a.admin.ch/V5.0/PublicServices.svc");
+
+        let config = VerificationConfig { ch_via_rest: true, ..Default::default() };
+        assert_eq!(
+            tax_id.verification_endpoint_with_config(&config),
+            "https://www.uid-wse-a.admin.ch/V5.0/PublicServices.svc/rest/ValidateVatNumber?vatNumber=CHE-116.281.710"
+        );
+    }
+
+    #[cfg(feature = "no_vat")]
+    #[test]
+    fn test_verification_endpoint_brreg() {
+        let tax_id = TaxId::new("NO974760673MVA").unwrap();
+        assert_eq!(tax_id.verification_endpoint(), "https://data.brreg.no/enhetsregisteret/api/enheter/974760673");
+
+        let config = VerificationConfig {
+            endpoints: Endpoints { brreg: Some("https://data.ppe.brreg.no/enhetsregisteret/api/enheter".to_string()), ..Default::default() },
+            ..Default::default()
+        };
+        assert_eq!(
+            tax_id.verification_endpoint_with_config(&config),
+            "https://data.ppe.brreg.no/enhetsregisteret/api/enheter/974760673"
+        );
+    }
+
+    #[cfg(feature = "eu_vat")]
+    #[test]
+    fn test_validate_checksum() {
+        assert_eq!(validate_checksum("DE", "136695976"), Ok(()));
+        assert_eq!(validate_checksum("DE", "136695977"), Err(ValidationError::InvalidChecksum));
+        assert_eq!(validate_checksum("DE", "13669597"), Err(ValidationError::InvalidSyntax));
+        assert_eq!(validate_checksum("AT", "U13585627"), Ok(()));
+        assert_eq!(validate_checksum("SE", "556703748501"), Err(ValidationError::ChecksumNotAvailable("SE".to_string())));
+        assert_eq!(validate_checksum("XX", "123456789"), Err(ValidationError::UnsupportedCountryCode("XX".to_string())));
+    }
+
+    #[cfg(feature = "gb_vat")]
+    #[test]
+    fn test_validate_checksum_gb_vat() {
+        assert_eq!(validate_checksum("GB", "591819014"), Err(ValidationError::ChecksumNotAvailable("GB".to_string())));
+    }
+
+    #[test]
+    fn test_enabled_features() {
+        let features = enabled_features();
+        assert_eq!(features.contains(&"eu_vat"), cfg!(feature = "eu_vat"));
+        assert_eq!(features.contains(&"gb_vat"), cfg!(feature = "gb_vat"));
+        assert_eq!(features.contains(&"ch_vat"), cfg!(feature = "ch_vat"));
+        assert_eq!(features.contains(&"no_vat"), cfg!(feature = "no_vat"));
+        assert_eq!(features.contains(&"sm_vat"), cfg!(feature = "sm_vat"));
+        assert_eq!(features.contains(&"rs_pib"), cfg!(feature = "rs_pib"));
+        assert_eq!(features.contains(&"ae_trn"), cfg!(feature = "ae_trn"));
+        assert_eq!(features.contains(&"ua_edrpou"), cfg!(feature = "ua_edrpou"));
+        assert_eq!(features.contains(&"tracing"), cfg!(feature = "tracing"));
+        assert_eq!(features.contains(&"iban"), cfg!(feature = "iban"));
+        assert_eq!(features.contains(&"csv"), cfg!(feature = "csv"));
+        assert_eq!(features.contains(&"async"), cfg!(feature = "async"));
+    }
+
+    #[cfg(all(feature = "eu_vat", not(feature = "gb_vat")))]
+    #[test]
+    fn test_enabled_features_default() {
+        assert!(enabled_features().starts_with(&["eu_vat"]));
+        assert!(!enabled_features().contains(&"gb_vat"));
+    }
+
+    #[test]
+    fn test_live_verification_countries() {
+        let countries = live_verification_countries();
+        assert!(countries.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(countries.contains(&"SE"), cfg!(feature = "eu_vat"));
+        assert_eq!(countries.contains(&"GB"), cfg!(feature = "gb_vat"));
+        assert_eq!(countries.contains(&"CH"), cfg!(feature = "ch_vat"));
+        assert_eq!(countries.contains(&"NO"), cfg!(feature = "no_vat"));
+        assert!(!countries.contains(&"SM"));
+    }
+
+    #[cfg(feature = "eu_vat")]
+    #[test]
+    fn test_has_live_verifier() {
+        assert!(TaxId::new("SE556703748501").unwrap().has_live_verifier());
+        assert!(TaxId::new("XI591819014").unwrap().has_live_verifier());
+    }
+
+    #[cfg(feature = "sm_vat")]
+    #[test]
+    fn test_has_live_verifier_sm_vat() {
+        assert!(!TaxId::new("SM24165").unwrap().has_live_verifier());
+        assert!(supported_countries().contains(&"SM"));
+        assert!(!live_verification_countries().contains(&"SM"));
+    }
+
+    #[cfg(feature = "rs_pib")]
+    #[test]
+    fn test_has_live_verifier_rs_pib() {
+        assert!(!TaxId::new("RS101134702").unwrap().has_live_verifier());
+        assert!(!live_verification_countries().contains(&"RS"));
+    }
+
+    #[cfg(feature = "ua_edrpou")]
+    #[test]
+    fn test_has_live_verifier_ua_edrpou() {
+        assert!(!TaxId::new("UA32855961").unwrap().has_live_verifier());
+        assert!(!live_verification_countries().contains(&"UA"));
+    }
+
+    #[cfg(feature = "ae_trn")]
+    #[test]
+    fn test_has_live_verifier_ae_trn() {
+        assert!(!TaxId::new("AE100235617900003").unwrap().has_live_verifier());
+        assert!(!live_verification_countries().contains(&"AE"));
+    }
+
+    #[test]
+    fn test_is_dummy() {
+        assert!(is_dummy("999999999"));
+        assert!(is_dummy("999999919"));
+        assert!(is_dummy("123456789"));
+        assert!(is_dummy("0123456789"));
+        assert!(is_dummy("987654321"));
+        assert!(is_dummy("7890123456"));
+        assert!(is_dummy("E-111.111.111"));
+        assert!(!is_dummy("556703748501"));
+        assert!(!is_dummy("100000001"));
+        assert!(!is_dummy("12345"));
+        assert!(!is_dummy("GD001"));
+    }
+
+    #[cfg(feature = "eu_vat")]
+    #[test]
+    fn test_new_rejecting_dummies_eu_vat() {
+        // Syntax only countries, placeholders in checksum countries mostly fail their checksum
+        for value in ["IT99999999999", "FR12345678901", "SE123456789101", "EL987654324", "NL123456789B01"] {
+            assert_eq!(TaxId::new(value).map(|_| ()), Ok(()), "{}", value);
+            assert_eq!(TaxId::new_rejecting_dummies(value).unwrap_err(), ValidationError::SuspectedDummy, "{}", value);
+        }
+
+        let tax_id = TaxId::new_rejecting_dummies("SE556703748501").unwrap();
+        assert!(!tax_id.is_suspected_dummy());
+    }
+
+    #[cfg(feature = "eu_vat")]
+    #[test]
+    fn test_examples_are_not_dummies() {
+        for code in supported_countries() {
+            let example = country_example(code).unwrap();
+            assert!(!TaxId::new(example).unwrap().is_suspected_dummy(), "{}", example);
+        }
+    }
+
+    #[cfg(feature = "gb_vat")]
+    #[test]
+    fn test_new_rejecting_dummies_gb_vat() {
+        assert_eq!(TaxId::new_rejecting_dummies("GB999999999").unwrap_err(), ValidationError::SuspectedDummy);
+        assert!(TaxId::new_rejecting_dummies("GB591819014").is_ok());
+        assert!(TaxId::new_rejecting_dummies("GBGD001").is_ok());
+    }
+
+    #[cfg(feature = "ch_vat")]
+    #[test]
+    fn test_new_rejecting_dummies_ch_vat() {
+        assert_eq!(TaxId::new_rejecting_dummies("CHE-123.456.789").unwrap_err(), ValidationError::SuspectedDummy);
+        assert!(TaxId::new_rejecting_dummies("CHE-116.281.710").is_ok());
+    }
+
+    #[cfg(feature = "no_vat")]
+    #[test]
+    fn test_new_rejecting_dummies_no_vat() {
+        assert_eq!(TaxId::new_rejecting_dummies("NO123456789MVA").unwrap_err(), ValidationError::SuspectedDummy);
+        assert!(TaxId::new_rejecting_dummies("NO974760673MVA").is_ok());
+    }
+
+    #[cfg(feature = "eu_vat")]
+    #[test]
+    fn test_new_restricted() {
+        let tax_id = TaxId::new_restricted("SE556703748501", &["SE", "DK"]).unwrap();
+        assert_eq!(tax_id.country_code(), "SE");
+
+        assert_eq!(
+            TaxId::new_restricted("EL094259216", &["SE", "DK"]).unwrap_err(),
+            ValidationError::CountryNotAllowed("GR".to_string())
+        );
+        assert!(TaxId::new_restricted("EL094259216", &["GR"]).is_ok());
+        assert_eq!(TaxId::new_restricted("SE55670374850", &["SE"]).unwrap_err(), ValidationError::InvalidSyntax);
+    }
+
+    #[cfg(feature = "eu_vat")]
+    #[test]
+    fn test_local_digits_eu_vat() {
+        assert_eq!(TaxId::new("ATU13585627").unwrap().local_digits(), "13585627");
+        assert_eq!(TaxId::new("IE6433435F").unwrap().local_digits(), "6433435");
+        assert_eq!(TaxId::new("IE8Z49289F").unwrap().local_digits(), "849289");
+        assert_eq!(TaxId::new("SE556703748501").unwrap().local_digits(), "556703748501");
+    }
+
+    #[cfg(feature = "ch_vat")]
+    #[test]
+    fn test_local_digits_ch_vat() {
+        assert_eq!(TaxId::new("CHE-116.281.710").unwrap().local_digits(), "116281710");
+        assert_eq!(TaxId::new("CHE-116.281.710 MWST").unwrap().local_digits(), "116281710");
+    }
+
+    #[cfg(feature = "no_vat")]
+    #[test]
+    fn test_local_digits_no_vat() {
+        assert_eq!(TaxId::new("NO974760673MVA").unwrap().local_digits(), "974760673");
+        assert_eq!(TaxId::new("NO974760673").unwrap().local_digits(), "974760673");
+    }
+
+    #[cfg(feature = "eu_vat")]
+    #[test]
+    fn test_to_iso_value() {
+        assert_eq!(TaxId::new("EL094259216").unwrap().to_iso_value(), "GR094259216");
+        assert_eq!(TaxId::new("XI591819014").unwrap().to_iso_value(), "GB591819014");
+        assert_eq!(TaxId::new("SE556703748501").unwrap().to_iso_value(), "SE556703748501");
+    }
+
+    #[cfg(feature = "ch_vat")]
+    #[test]
+    fn test_to_iso_value_ch_vat() {
+        assert_eq!(TaxId::new("CHE-116.281.710 MWST").unwrap().to_iso_value(), "CHE-116.281.710 MWST");
+    }
+
+    #[test]
+    fn test_service_health_unsupported() {
+        assert!(matches!(
+            service_health("XX"),
+            Err(VerificationError::UnsupportedCountryCode(code)) if code == "XX"
+        ));
+    }
+
+    #[cfg(feature = "sm_vat")]
+    #[test]
+    fn test_service_health_without_live_verifier() {
+        assert!(!service_health("SM").unwrap());
+    }
+
+    #[cfg(feature = "eu_vat")]
+    #[test]
+    fn test_branch_suffix_eu_vat() {
+        assert_eq!(TaxId::new("NL004495445B01").unwrap().branch_suffix(), Some("B01"));
+        assert_eq!(TaxId::new("NL004495445B02").unwrap().branch_suffix(), Some("B02"));
+        assert_eq!(TaxId::new("SE556703748501").unwrap().branch_suffix(), Some("01"));
+        assert_eq!(TaxId::new("XI591819014001").unwrap().branch_suffix(), Some("001"));
+        assert_eq!(TaxId::new("XI591819014").unwrap().branch_suffix(), None);
+        assert_eq!(TaxId::new("DE136695976").unwrap().branch_suffix(), None);
+    }
+
+    #[cfg(feature = "gb_vat")]
+    #[test]
+    fn test_branch_suffix_gb_vat() {
+        assert_eq!(TaxId::new("GB591819014001").unwrap().branch_suffix(), Some("001"));
+        assert_eq!(TaxId::new("GB591819014").unwrap().branch_suffix(), None);
+        assert_eq!(TaxId::new("GBGD001").unwrap().branch_suffix(), None);
+    }
+
+    #[cfg(feature = "no_vat")]
+    #[test]
+    fn test_branch_suffix_no_vat() {
+        assert_eq!(TaxId::new("NO974760673MVA").unwrap().branch_suffix(), None);
+    }
+
+    #[cfg(feature = "eu_vat")]
+    #[test]
+    fn test_masked() {
+        assert_eq!(TaxId::new("SE556703748501").unwrap().masked(), "SE**********01");
+        assert_eq!(TaxId::new("ATU13585627").unwrap().masked(), "AT*******27");
+        assert_eq!(TaxId::new("EL094259216").unwrap().masked(), "EL*******16");
+    }
+
+    #[cfg(feature = "gb_vat")]
+    #[test]
+    fn test_masked_short_values() {
+        assert_eq!(TaxId::new("GBGD001").unwrap().masked(), "GB***01");
+        assert_eq!(TaxId::new("GB591819014").unwrap().masked(), "GB*******14");
+    }
+
+    #[cfg(feature = "ch_vat")]
+    #[test]
+    fn test_masked_ch_vat() {
+        assert_eq!(TaxId::new("CHE-116.281.710").unwrap().masked(), "CH***********10");
+    }
+
+    #[cfg(feature = "eu_vat")]
+    #[test]
+    fn test_into_parts_eu_vat() {
+        let tax_id = TaxId::new("EL094259216").unwrap();
+        let expected = TaxIdParts {
+            value: tax_id.value().to_string(),
+            country_code: tax_id.country_code().to_string(),
+            tax_country_code: tax_id.tax_country_code().to_string(),
+            local_value: tax_id.local_value().to_string(),
+            id_type_name: tax_id.tax_id_type().to_string(),
+        };
+
+        let parts = tax_id.into_parts();
+        assert_eq!(parts, expected);
+        assert_eq!(parts.country_code, "GR");
+        assert_eq!(parts.tax_country_code, "EL");
+        assert_eq!(parts.id_type_name, "eu_vat");
+    }
+
+    #[cfg(feature = "gb_vat")]
+    #[test]
+    fn test_into_parts_gb_vat() {
+        let parts = TaxId::new("GB591819014").unwrap().into_parts();
+        assert_eq!(parts, TaxIdParts {
+            value: "GB591819014".to_string(),
+            country_code: "GB".to_string(),
+            tax_country_code: "GB".to_string(),
+            local_value: "591819014".to_string(),
+            id_type_name: "gb_vat".to_string(),
+        });
+    }
+
+    #[test]
+    fn test_local_value_lengths_match_examples() {
+        for tax_country_code in SYNTAX.keys() {
+            let example = TaxId::new(country_example(tax_country_code).unwrap()).unwrap();
+            let lengths = local_value_lengths(tax_country_code).unwrap();
+            assert!(lengths.contains(&example.local_value().len()), "{}", tax_country_code);
+        }
+    }
+
+    #[cfg(feature = "eu_vat")]
+    #[test]
+    fn test_local_value_lengths_eu_vat() {
+        assert_eq!(local_value_lengths("SE"), Some(vec![12]));
+        assert_eq!(local_value_lengths("CZ"), Some(vec![8, 9, 10]));
+        assert_eq!(local_value_lengths("US"), None);
+    }
+
+    #[cfg(feature = "gb_vat")]
+    #[test]
+    fn test_local_value_lengths_gb_vat() {
+        assert_eq!(local_value_lengths("GB"), Some(vec![9, 12, 5]));
+    }
+
+    #[cfg(feature = "eu_vat")]
+    #[test]
+    fn test_classify_eu_vat() {
+        assert_eq!(TaxId::classify("SE123456789101"), Ok("eu_vat"));
+        assert_eq!(TaxId::classify("XI591819014"), Ok("eu_vat"));
+        assert_eq!(TaxId::classify("SE12345678910"), Err(ValidationError::InvalidSyntax));
+        assert_eq!(TaxId::classify("US123456789"), Err(ValidationError::UnsupportedCountryCode("US".to_string())));
+    }
+
+    #[cfg(feature = "gb_vat")]
+    #[test]
+    fn test_classify_gb_vat() {
+        assert_eq!(TaxId::classify("GB591819014"), Ok("gb_vat"));
+    }
+
+    #[cfg(feature = "ch_vat")]
+    #[test]
+    fn test_classify_ch_vat() {
+        assert_eq!(TaxId::classify("CHE123456789"), Ok("ch_vat"));
+    }
+
+    #[cfg(feature = "no_vat")]
+    #[test]
+    fn test_classify_no_vat() {
+        assert_eq!(TaxId::classify("NO123456789"), Ok("no_vat"));
+    }
+
+    #[cfg(feature = "eu_vat")]
+    #[test]
+    fn test_validate_syntax_many() {
+        let results = TaxId::validate_syntax_many(vec!["SE123456789101", "US123456789", "SE12345678910"]);
+        assert_eq!(results, vec![
+            ("SE123456789101".to_string(), Ok(())),
+            ("US123456789".to_string(), Err(ValidationError::UnsupportedCountryCode("US".to_string()))),
+            ("SE12345678910".to_string(), Err(ValidationError::InvalidSyntax)),
+        ]);
+
+        let owned = vec!["ATU13585627".to_string()];
+        assert_eq!(TaxId::validate_syntax_many(&owned), vec![("ATU13585627".to_string(), Ok(()))]);
+    }
+
+    #[cfg(feature = "eu_vat")]
+    #[test]
+    fn test_new_with_report() {
+        let (tax_id, normalizations) = TaxId::new_with_report("SE123456789101").unwrap();
+        assert_eq!(tax_id.value(), "SE123456789101");
+        assert_eq!(normalizations, vec![]);
+
+        let (tax_id, normalizations) = TaxId::new_with_report("  SE123456789101\n").unwrap();
+        assert_eq!(tax_id.value(), "SE123456789101");
+        assert_eq!(normalizations, vec![Normalization::TrimmedWhitespace]);
+
+        let (tax_id, normalizations) = TaxId::new_with_report("atu13585627").unwrap();
+        assert_eq!(tax_id.value(), "ATU13585627");
+        assert_eq!(normalizations, vec![Normalization::UpperCased]);
+
+        let (tax_id, normalizations) = TaxId::new_with_report("SE 1234-5678-9101").unwrap();
+        assert_eq!(tax_id.value(), "SE123456789101");
+        assert_eq!(normalizations, vec![Normalization::RemovedSeparators]);
+
+        let (tax_id, normalizations) = TaxId::new_with_report(" se 123456789101").unwrap();
+        assert_eq!(tax_id.value(), "SE123456789101");
+        assert_eq!(normalizations, vec![
+            Normalization::TrimmedWhitespace,
+            Normalization::UpperCased,
+            Normalization::RemovedSeparators,
+        ]);
+    }
+
+    #[cfg(feature = "ch_vat")]
+    #[test]
+    fn test_new_with_report_ch_vat() {
+        let (tax_id, normalizations) = TaxId::new_with_report("che-123.456.789 mwst ").unwrap();
+        assert_eq!(tax_id.value(), "CHE-123.456.789 MWST");
+        assert_eq!(normalizations, vec![Normalization::TrimmedWhitespace, Normalization::UpperCased]);
+    }
+}
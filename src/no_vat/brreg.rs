@@ -1,8 +1,12 @@
 use std::collections::HashMap;
 use lazy_static::lazy_static;
-use reqwest::header::{HeaderMap, HeaderValue, ACCEPT};
+use reqwest::header::{HeaderMap, HeaderValue, ACCEPT, USER_AGENT};
 use serde_json::{json, Value};
-use crate::verification::{Verifier, Verification, VerificationStatus, VerificationResponse};
+#[cfg(target_arch = "wasm32")]
+use crate::verification::ResponseFuture;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::verification::blocking_client;
+use crate::verification::{record_endpoint, user_agent, VerificationConfig, Verifier, Verification, VerificationStatus, VerificationResponse, Provider};
 use crate::verification::VerificationStatus::{*};
 use crate::errors::VerificationError;
 use crate::no_vat::NoVat;
@@ -15,7 +19,7 @@ use crate::verification::UnavailableReason::{ServiceUnavailable};
 // https://data.brreg.no/enhetsregisteret/oppslag/enheter
 // https://data.brreg.no/enhetsregisteret/api/dokumentasjon/no/index.html#tag/Enheter/operation/hentEnhet
 
-static BASE_URI: &'static str = "https://data.brreg.no/enhetsregisteret/api/enheter";
+static BASE_URI: &str = "https://data.brreg.no/enhetsregisteret/api/enheter";
 
 lazy_static! {
     #[derive(Debug)]
@@ -40,6 +44,23 @@ lazy_static! {
 pub struct BrReg;
 
 impl BrReg {
+    fn uri(tax_id: &TaxId, config: &VerificationConfig) -> String {
+        let base_uri = config.endpoints.brreg.as_deref().unwrap_or(BASE_URI);
+        format!("{}/{}", base_uri, NoVat::extract_org_number(&NoVat, tax_id))
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn blocking_request(tax_id: &TaxId, config: &VerificationConfig) -> Result<reqwest::blocking::RequestBuilder, VerificationError> {
+        let uri = BrReg::uri(tax_id, config);
+        record_endpoint(&uri);
+        let request = blocking_client(config)?
+            .get(uri)
+            .header(USER_AGENT, user_agent(config))
+            .headers(HEADERS.clone());
+
+        Ok(request)
+    }
+
     fn qualify(&self, hash: &serde_json::Map<String, serde_json::Value>) -> VerificationStatus {
         let mut valid = true;
         for (key, value) in REQUIREMENTS_TO_BE_VALID.iter() {
@@ -59,11 +80,9 @@ impl BrReg {
 }
 
 impl Verifier for BrReg {
-    fn make_request(&self, tax_id: &TaxId) -> Result<VerificationResponse, VerificationError> {
-        let client = reqwest::blocking::Client::new();
-        let res = client
-            .get(format!("{}/{}", BASE_URI, NoVat::extract_org_number(&NoVat, tax_id)))
-            .headers(HEADERS.clone())
+    #[cfg(not(target_arch = "wasm32"))]
+    fn make_request(&self, tax_id: &TaxId, config: &VerificationConfig) -> Result<VerificationResponse, VerificationError> {
+        let res = BrReg::blocking_request(tax_id, config)?
             .send()
             .map_err(VerificationError::HttpError)?;
 
@@ -75,16 +94,58 @@ impl Verifier for BrReg {
         )
     }
 
+    #[cfg(target_arch = "wasm32")]
+    fn make_request_async<'a>(&'a self, tax_id: &'a TaxId, config: &'a VerificationConfig) -> ResponseFuture<'a> {
+        Box::pin(async move {
+            let uri = BrReg::uri(tax_id, config);
+            record_endpoint(&uri);
+            let res = reqwest::Client::new()
+                .get(uri)
+                .header(USER_AGENT, user_agent(config))
+                .headers(HEADERS.clone())
+                .send()
+                .await
+                .map_err(VerificationError::HttpError)?;
+
+            Ok(
+                VerificationResponse::new(
+                    res.status().as_u16(),
+                    res.text().await.map_err(VerificationError::HttpError)?
+                )
+            )
+        })
+    }
+
+    fn provider(&self) -> Provider {
+        Provider::BrReg
+    }
+
+    fn endpoint(&self, tax_id: &TaxId, config: &VerificationConfig) -> String {
+        BrReg::uri(tax_id, config)
+    }
+
     fn parse_response(&self, response: VerificationResponse) -> Result<Verification, VerificationError> {
         match response.status() {
-            404 | 410 => return Ok(
+            404 => Ok(
                 Verification::new(
-                    Unverified, json!({})
+                    Unverified, json!({"reason": "not_found"})
                 )
             ),
+            410 => {
+                // Struck off entities keep a minimal record with their deletion date
+                let deletion_date = serde_json::from_str::<Value>(response.body()).ok()
+                    .and_then(|v| v.get("slettedato").cloned());
+
+                let mut data = json!({"reason": "deleted"});
+                if let Some(deletion_date) = deletion_date {
+                    data["deletionDate"] = deletion_date;
+                }
+
+                Ok(Verification::new(Unverified, data))
+            },
             200 | 500 => {
                 let mut v: Value = serde_json::from_str(response.body())
-                    .map_err(VerificationError::JsonParsingError)?;
+                    .map_err(|e| VerificationError::json("BrReg", response.body(), e))?;
                 translate_keys(&mut v);
                 let hash = v.as_object().unwrap();
 
@@ -104,39 +165,122 @@ impl Verifier for BrReg {
                     )
                 )
             },
-            _ => return Err(VerificationError::UnexpectedStatusCode(response.status())),
+            _ => Err(VerificationError::UnexpectedStatusCode(response.status())),
         }
     }
+
+    fn parse_response_with_config(&self, response: VerificationResponse, config: &VerificationConfig) -> Result<Verification, VerificationError> {
+        let raw = match response.status() {
+            200 | 500 if config.brreg_keep_original_keys => serde_json::from_str::<Value>(response.body()).ok(),
+            _ => None,
+        };
+        let mut verification = self.parse_response(response)?;
+
+        if let Some(raw) = raw {
+            let translated = verification.data().clone();
+            *verification.data_mut() = json!({
+                "raw": raw,
+                "translated": translated,
+            });
+        }
+
+        Ok(verification)
+    }
+
+    /// A superseded organisation number can be redirected to another entity.
+    /// The returned entity is still qualified, but the requested number is kept in the data.
+    /// An MVA suffix on an entity the registry lists as not VAT registered is flagged as a mismatch.
+    fn reconcile(&self, tax_id: &TaxId, mut verification: Verification) -> Verification {
+        let requested = NoVat::extract_org_number(&NoVat, tax_id);
+        let data = verification.data();
+        let translated = data.get("translated").unwrap_or(data);
+        let returned = translated.get("organizationNumber").and_then(|v| v.as_str());
+        let vat_registered = translated.get("registeredInVatRegister").and_then(|v| v.as_bool());
+
+        if returned.is_some_and(|returned| returned != requested) {
+            verification.data_mut()["requestedOrganizationNumber"] = json!(requested);
+        }
+
+        if tax_id.local_value().ends_with("MVA") && vat_registered == Some(false) {
+            verification.data_mut()["suffixMismatch"] = json!(true);
+        }
+
+        verification
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::no_vat::translator::untranslated_keys;
+
+    const VERIFIED_BODY: &str = r#"{
+        "organisasjonsnummer": "123456789",
+        "navn": "Test Company AS",
+        "registrertIMvaregisteret": true,
+        "konkurs": false,
+        "underAvvikling": false,
+        "underTvangsavviklingEllerTvangsopplosning": false,
+        "forretningsadresse": {
+            "land": "Norge",
+            "landkode": "NO",
+            "postnummer": "0151",
+            "poststed": "OSLO",
+            "adresse": [
+                "Grev Wedels plass 9"
+            ],
+            "kommune": "OSLO",
+            "kommunenummer": "0301"
+        }
+    }"#;
+
+    #[cfg(feature = "no_vat")]
+    #[test]
+    fn test_sample_responses_are_fully_translated() {
+        let verified: Value = serde_json::from_str(VERIFIED_BODY).unwrap();
+        assert_eq!(untranslated_keys(&verified), Vec::<String>::new());
+
+        let deleted = json!({
+            "organisasjonsnummer": "123456789",
+            "slettedato": "2024-03-09",
+            "_links": { "self": {} }
+        });
+        assert_eq!(untranslated_keys(&deleted), Vec::<String>::new());
+    }
+
+    #[cfg(feature = "no_vat")]
+    #[test]
+    fn test_parse_verification() {
+        let tax_id = TaxId::new("NO123456789MVA").unwrap();
+
+        let verification = tax_id.parse_verification(200, VERIFIED_BODY).unwrap();
+        assert_eq!(verification.status(), &Verified);
+        assert_eq!(verification.data()["name"], "Test Company AS");
+        assert_eq!(verification.provider(), Provider::BrReg);
+
+        let verification = tax_id.parse_verification(404, "{}").unwrap();
+        assert_eq!(verification.status(), &Unverified);
+    }
+
+    #[cfg(feature = "no_vat")]
+    #[test]
+    fn test_request_user_agent() {
+        let tax_id = TaxId::new("NO123456789").unwrap();
+
+        let request = BrReg::blocking_request(&tax_id, &VerificationConfig::default()).unwrap().build().unwrap();
+        assert_eq!(request.headers()[USER_AGENT], concat!("tax-ids/", env!("CARGO_PKG_VERSION")));
+
+        let config = VerificationConfig { user_agent: Some("my-shop/1.0".to_string()), ..Default::default() };
+        let request = BrReg::blocking_request(&tax_id, &config).unwrap().build().unwrap();
+        assert_eq!(request.headers()[USER_AGENT], "my-shop/1.0");
+    }
 
     #[cfg(feature = "no_vat")]
     #[test]
     fn test_parse_response_verified() {
         let response = VerificationResponse::new(
             200,
-            r#"{
-                "organisasjonsnummer": "123456789",
-                "navn": "Test Company AS",
-                "registrertIMvaregisteret": true,
-                "konkurs": false,
-                "underAvvikling": false,
-                "underTvangsavviklingEllerTvangsopplosning": false,
-                "forretningsadresse": {
-                    "land": "Norge",
-                    "landkode": "NO",
-                    "postnummer": "0151",
-                    "poststed": "OSLO",
-                    "adresse": [
-                        "Grev Wedels plass 9"
-                    ],
-                    "kommune": "OSLO",
-                    "kommunenummer": "0301"
-                }
-            }"#.to_string()
+            VERIFIED_BODY.to_string()
         );
 
         let verifier = BrReg;
@@ -163,6 +307,120 @@ mod tests {
         }));
     }
 
+    #[cfg(feature = "no_vat")]
+    #[test]
+    fn test_parse_response_keep_original_keys() {
+        let body = r#"{
+            "organisasjonsnummer": "123456789",
+            "navn": "Test Company AS",
+            "registrertIMvaregisteret": true,
+            "konkurs": false,
+            "underAvvikling": false,
+            "underTvangsavviklingEllerTvangsopplosning": false
+        }"#;
+        let config = VerificationConfig { brreg_keep_original_keys: true, ..Default::default() };
+
+        let verifier = BrReg;
+        let response = VerificationResponse::new(200, body.to_string());
+        let verification = verifier.parse_response_with_config(response, &config).unwrap();
+        assert_eq!(verification.status(), &Verified);
+        assert_eq!(verification.data(), &json!({
+            "raw": {
+                "organisasjonsnummer": "123456789",
+                "navn": "Test Company AS",
+                "registrertIMvaregisteret": true,
+                "konkurs": false,
+                "underAvvikling": false,
+                "underTvangsavviklingEllerTvangsopplosning": false
+            },
+            "translated": {
+                "organizationNumber": "123456789",
+                "name": "Test Company AS",
+                "registeredInVatRegister": true,
+                "bankruptcy": false,
+                "underLiquidation": false,
+                "underForcedLiquidation": false
+            }
+        }));
+
+        let tax_id = TaxId::new("NO987654321").unwrap();
+        let verification = verifier.reconcile(&tax_id, verification);
+        assert_eq!(verification.data().get("requestedOrganizationNumber").unwrap(), "987654321");
+
+        let response = VerificationResponse::new(200, body.to_string());
+        let verification = verifier.parse_response_with_config(response, &VerificationConfig::default()).unwrap();
+        assert_eq!(verification.data().get("raw"), None);
+        assert_eq!(verification.data().get("organizationNumber").unwrap(), "123456789");
+    }
+
+    #[cfg(feature = "no_vat")]
+    #[test]
+    fn test_reconcile_redirected_org_number() {
+        let response = VerificationResponse::new(
+            200,
+            r#"{
+                "organisasjonsnummer": "987654321",
+                "navn": "Successor Company AS",
+                "registrertIMvaregisteret": true,
+                "konkurs": false,
+                "underAvvikling": false,
+                "underTvangsavviklingEllerTvangsopplosning": false
+            }"#.to_string()
+        );
+
+        let tax_id = TaxId::new("NO123456789MVA").unwrap();
+        let verifier = BrReg;
+        let verification = verifier.reconcile(&tax_id, verifier.parse_response(response).unwrap());
+        assert_eq!(verification.status(), &Verified);
+        assert_eq!(verification.data().get("organizationNumber").unwrap(), "987654321");
+        assert_eq!(verification.data().get("requestedOrganizationNumber").unwrap(), "123456789");
+    }
+
+    #[cfg(feature = "no_vat")]
+    #[test]
+    fn test_reconcile_matching_org_number() {
+        let response = VerificationResponse::new(
+            200,
+            r#"{
+                "organisasjonsnummer": "123456789",
+                "registrertIMvaregisteret": true,
+                "konkurs": false,
+                "underAvvikling": false,
+                "underTvangsavviklingEllerTvangsopplosning": false
+            }"#.to_string()
+        );
+
+        let tax_id = TaxId::new("NO123456789").unwrap();
+        let verifier = BrReg;
+        let verification = verifier.reconcile(&tax_id, verifier.parse_response(response).unwrap());
+        assert_eq!(verification.data().get("requestedOrganizationNumber"), None);
+    }
+
+    #[cfg(feature = "no_vat")]
+    #[test]
+    fn test_reconcile_mva_suffix_mismatch() {
+        let body = r#"{
+            "organisasjonsnummer": "123456789",
+            "registrertIMvaregisteret": false,
+            "konkurs": false,
+            "underAvvikling": false,
+            "underTvangsavviklingEllerTvangsopplosning": false
+        }"#;
+        let verifier = BrReg;
+
+        let tax_id = TaxId::new("NO123456789MVA").unwrap();
+        let response = VerificationResponse::new(200, body.to_string());
+        let verification = verifier.reconcile(&tax_id, verifier.parse_response(response).unwrap());
+        assert_eq!(verification.status(), &Unverified);
+        assert_eq!(verification.data().get("suffixMismatch").unwrap(), true);
+
+        let tax_id = TaxId::new("NO123456789").unwrap();
+        let response = VerificationResponse::new(200, body.to_string());
+        let verification = verifier.reconcile(&tax_id, verifier.parse_response(response).unwrap());
+        assert_eq!(verification.status(), &Unverified);
+        assert_eq!(verification.data().get("suffixMismatch"), None);
+    }
+
     #[cfg(feature = "no_vat")]
     #[test]
     fn test_parse_response_unverified_due_to_not_found() {
@@ -174,7 +432,7 @@ mod tests {
         let verifier = BrReg;
         let verification = verifier.parse_response(response).unwrap();
         assert_eq!(verification.status(), &Unverified);
-        assert_eq!(verification.data(), &json!({}));
+        assert_eq!(verification.data(), &json!({"reason": "not_found"}));
     }
 
     #[cfg(feature = "no_vat")]
@@ -224,7 +482,24 @@ mod tests {
         let verifier = BrReg;
         let verification = verifier.parse_response(response).unwrap();
         assert_eq!(verification.status(), &Unverified);
-        assert_eq!(verification.data(), &json!({}));
+        assert_eq!(verification.data(), &json!({
+            "reason": "deleted",
+            "deletionDate": "2024-03-09"
+        }));
+    }
+
+    #[cfg(feature = "no_vat")]
+    #[test]
+    fn test_parse_response_unverified_due_to_deleted_without_body() {
+        let response = VerificationResponse::new(
+            410,
+            "".to_string()
+        );
+
+        let verifier = BrReg;
+        let verification = verifier.parse_response(response).unwrap();
+        assert_eq!(verification.status(), &Unverified);
+        assert_eq!(verification.data(), &json!({"reason": "deleted"}));
     }
 
     #[cfg(feature = "no_vat")]
@@ -267,7 +542,7 @@ mod tests {
 
         let verifier = BrReg;
         let verification = verifier.parse_response(response);
-        assert_eq!(verification.is_err(), true);
+        assert!(verification.is_err());
         match verification {
             Err(VerificationError::UnexpectedStatusCode(code)) => {
                 assert_eq!(code, 204);
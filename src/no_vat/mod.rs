@@ -1,6 +1,8 @@
 mod brreg;
 mod translator;
 
+pub use translator::untranslated_keys;
+
 use std::collections::HashMap;
 use lazy_static::lazy_static;
 use regex::Regex;
@@ -21,7 +23,7 @@ pub struct NoVat;
 
 impl NoVat {
     pub fn extract_org_number(&self, tax_id: &TaxId) -> String {
-        tax_id.local_value().replace("MVA", "")
+        tax_id.local_digits()
     }
 }
 
@@ -29,7 +31,20 @@ impl TaxIdType for NoVat {
     fn name(&self) -> &'static str {
         "no_vat"
     }
-    fn syntax_map(&self) -> &HashMap<String, Regex> {
+
+    fn human_name(&self) -> &'static str {
+        "Norwegian VAT number (MVA)"
+    }
+
+    fn example(&self, tax_country_code: &str) -> Option<&'static str> {
+        (tax_country_code == "NO").then_some("NO974760673MVA")
+    }
+
+    fn local_value_lengths(&self, tax_country_code: &str) -> Option<&'static [usize]> {
+        // The organisation number, optionally followed by MVA
+        (tax_country_code == "NO").then_some(&[9, 12])
+    }
+    fn syntax_map(&self) -> &'static HashMap<String, Regex> {
         &NO_VAT_PATTERN
     }
 
@@ -37,8 +52,8 @@ impl TaxIdType for NoVat {
         tax_country_code.to_string()
     }
 
-    fn verifier(&self) -> Box<dyn Verifier> {
-        Box::new(brreg::BrReg)
+    fn verifier(&self) -> &'static dyn Verifier {
+        &brreg::BrReg
     }
 }
 
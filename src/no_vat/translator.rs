@@ -1,6 +1,5 @@
 use std::collections::HashMap;
 use lazy_static::lazy_static;
-use toml;
 use serde_json::{Value, Map};
 
 lazy_static!(
@@ -12,6 +11,39 @@ lazy_static!(
     };
 );
 
+/// Returns the keys of a BrReg response that have no English translation, sorted and
+/// without duplicates. Useful to detect when the registry adds fields this crate
+/// doesn't map yet. HAL keys like `_links` and their children are ignored.
+pub fn untranslated_keys(value: &Value) -> Vec<String> {
+    let mut keys = Vec::new();
+    collect_untranslated_keys(value, &mut keys);
+    keys.sort();
+    keys.dedup();
+    keys
+}
+
+fn collect_untranslated_keys(value: &Value, keys: &mut Vec<String>) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map.iter() {
+                if key.starts_with('_') {
+                    continue;
+                }
+                if !TRANSLATIONS.contains_key(key) {
+                    keys.push(key.clone());
+                }
+                collect_untranslated_keys(value, keys);
+            }
+        }
+        Value::Array(vec) => {
+            for value in vec {
+                collect_untranslated_keys(value, keys);
+            }
+        }
+        _ => {}
+    }
+}
+
 pub fn translate_keys(obj: &mut Value) {
     let translations = &*TRANSLATIONS;
 
@@ -34,3 +66,26 @@ pub fn translate_keys(obj: &mut Value) {
         _ => {}
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use super::*;
+
+    #[cfg(feature = "no_vat")]
+    #[test]
+    fn test_untranslated_keys() {
+        let value = json!({
+            "organisasjonsnummer": "123456789",
+            "nyttFelt": true,
+            "forretningsadresse": {
+                "land": "Norge",
+                "gatenavn": "Grev Wedels plass"
+            },
+            "aktivitet": [{ "nyttFelt": 1 }],
+            "_links": { "self": { "href": "https://data.brreg.no" } }
+        });
+
+        assert_eq!(untranslated_keys(&value), vec!["gatenavn".to_string(), "nyttFelt".to_string()]);
+    }
+}
@@ -0,0 +1,113 @@
+use serde_json::json;
+
+use crate::errors::VerificationError;
+use crate::TaxId;
+#[cfg(target_arch = "wasm32")]
+use crate::verification::ResponseFuture;
+use crate::verification::{VerificationConfig, Verification, VerificationResponse, VerificationStatus, UnavailableReason, Verifier, Provider};
+
+// INFO(2026-10-15 agent):
+// For countries without a public lookup service this crate can query, like San Marino
+// which isn't covered by VIES, no request is performed and the verification is always unavailable.
+
+#[derive(Debug)]
+pub struct Offline;
+
+impl Offline {
+    /// Why the country can't be verified online.
+    pub fn note(tax_country_code: &str) -> &'static str {
+        match tax_country_code {
+            "SM" => "San Marino VAT numbers can't be verified online, VIES doesn't cover SM",
+            "RS" => "Serbian PIBs can't be verified online, the Tax Administration has no public lookup service",
+            "UA" => "Ukrainian EDRPOU codes can't be verified online, the state register has no public lookup service",
+            "AE" => "UAE TRNs can't be verified online, the Federal Tax Authority has no public verification API",
+            _ => "No online verification service is available for this country",
+        }
+    }
+}
+
+impl Verifier for Offline {
+    #[cfg(not(target_arch = "wasm32"))]
+    fn make_request(&self, _tax_id: &TaxId, _config: &VerificationConfig) -> Result<VerificationResponse, VerificationError> {
+        Ok(VerificationResponse::new(200, String::new()))
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn make_request_async<'a>(&'a self, _tax_id: &'a TaxId, _config: &'a VerificationConfig) -> ResponseFuture<'a> {
+        Box::pin(async move { Ok(VerificationResponse::new(200, String::new())) })
+    }
+
+    fn provider(&self) -> Provider {
+        Provider::Offline
+    }
+
+    /// Empty, as no request is performed.
+    fn endpoint(&self, _tax_id: &TaxId, _config: &VerificationConfig) -> String {
+        String::new()
+    }
+
+    fn parse_response(&self, _response: VerificationResponse) -> Result<Verification, VerificationError> {
+        Ok(
+            Verification::new(
+                VerificationStatus::Unavailable(UnavailableReason::ServiceUnavailable),
+                json!({})
+            )
+        )
+    }
+
+    fn reconcile(&self, tax_id: &TaxId, mut verification: Verification) -> Verification {
+        verification.data_mut()["note"] = json!(Offline::note(tax_id.tax_country_code()));
+        verification
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "sm_vat")]
+    #[test]
+    fn test_verify_is_unavailable_sm_vat() {
+        let tax_id = TaxId::new("SM24165").unwrap();
+        let verification = tax_id.verify().unwrap();
+
+        assert_eq!(verification.status(), &VerificationStatus::Unavailable(UnavailableReason::ServiceUnavailable));
+        assert_eq!(verification.data(), &json!({ "note": Offline::note("SM") }));
+        assert_eq!(verification.provider(), Provider::Offline);
+        assert_eq!(tax_id.verification_endpoint(), "");
+    }
+
+    #[cfg(feature = "rs_pib")]
+    #[test]
+    fn test_verify_is_unavailable_rs_pib() {
+        let tax_id = TaxId::new("RS101134702").unwrap();
+        let verification = tax_id.verify().unwrap();
+
+        assert_eq!(verification.status(), &VerificationStatus::Unavailable(UnavailableReason::ServiceUnavailable));
+        assert_eq!(verification.data(), &json!({ "note": Offline::note("RS") }));
+        assert_eq!(verification.provider(), Provider::Offline);
+        assert_eq!(tax_id.verification_endpoint(), "");
+    }
+
+    #[cfg(feature = "ua_edrpou")]
+    #[test]
+    fn test_verify_is_unavailable_ua_edrpou() {
+        let tax_id = TaxId::new("UA32855961").unwrap();
+        let verification = tax_id.verify().unwrap();
+
+        assert_eq!(verification.status(), &VerificationStatus::Unavailable(UnavailableReason::ServiceUnavailable));
+        assert_eq!(verification.data(), &json!({ "note": Offline::note("UA") }));
+        assert_eq!(verification.provider(), Provider::Offline);
+    }
+
+    #[cfg(feature = "ae_trn")]
+    #[test]
+    fn test_verify_is_unavailable_ae_trn() {
+        let tax_id = TaxId::new("AE100235617900003").unwrap();
+        let verification = tax_id.verify().unwrap();
+
+        assert_eq!(verification.status(), &VerificationStatus::Unavailable(UnavailableReason::ServiceUnavailable));
+        assert_eq!(verification.data(), &json!({ "note": Offline::note("AE") }));
+        assert_eq!(verification.provider(), Provider::Offline);
+    }
+}
@@ -0,0 +1,107 @@
+use std::collections::HashMap;
+use lazy_static::lazy_static;
+use regex::Regex;
+use crate::TaxIdType;
+use crate::checksum::{digits, mod11_10};
+use crate::errors::ValidationError;
+use crate::offline::Offline;
+use crate::verification::Verifier;
+
+lazy_static! {
+    #[derive(Debug)]
+    pub static ref RS_PIB_PATTERN: HashMap<String, Regex> = {
+        let mut m = HashMap::new();
+        m.insert("RS".to_string(), Regex::new(r"^RS[0-9]{9}$").unwrap());
+        m
+    };
+}
+
+#[derive(Debug)]
+pub struct RsPib;
+
+impl TaxIdType for RsPib {
+    fn name(&self) -> &'static str {
+        "rs_pib"
+    }
+
+    fn human_name(&self) -> &'static str {
+        "Serbian tax identification number (PIB)"
+    }
+
+    fn example(&self, tax_country_code: &str) -> Option<&'static str> {
+        (tax_country_code == "RS").then_some("RS101134702")
+    }
+
+    fn local_value_lengths(&self, tax_country_code: &str) -> Option<&'static [usize]> {
+        (tax_country_code == "RS").then_some(&[9])
+    }
+
+    fn syntax_map(&self) -> &'static HashMap<String, Regex> {
+        &RS_PIB_PATTERN
+    }
+
+    /// ISO 7064 MOD 11,10 over the first eight digits, the ninth being the check digit.
+    fn validate_checksum(&self, value: &str) -> Result<(), ValidationError> {
+        let digits = digits(value);
+        if mod11_10(&digits[..8]) != digits[8] {
+            return Err(ValidationError::InvalidChecksum);
+        }
+        Ok(())
+    }
+
+    fn has_checksum(&self, tax_country_code: &str) -> bool {
+        tax_country_code == "RS"
+    }
+
+    fn country_code_from_tax_country(&self, tax_country_code: &str) -> String {
+        tax_country_code.to_string()
+    }
+
+    fn verifier(&self) -> &'static dyn Verifier {
+        &Offline
+    }
+
+    fn has_live_verifier(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TaxId;
+
+    #[cfg(feature = "rs_pib")]
+    #[test]
+    fn test_rs_pibs() {
+        let valid_vat_numbers = vec![
+            "RS101134702",
+            "RS100002436",
+        ];
+        let invalid_vat_numbers = vec![
+            "RS10113470",
+            "RS1011347021",
+            "RS10113470A",
+            "RS 101134702",
+        ];
+
+        for valid in valid_vat_numbers {
+            assert!(RsPib::validate_syntax(&RsPib, valid).is_ok());
+        }
+
+        for invalid in invalid_vat_numbers {
+            assert!(RsPib::validate_syntax(&RsPib, invalid).is_err());
+        }
+    }
+
+    #[cfg(feature = "rs_pib")]
+    #[test]
+    fn test_validate_checksum() {
+        assert_eq!(RsPib.validate_checksum("RS101134702"), Ok(()));
+        assert_eq!(RsPib.validate_checksum("RS106884584"), Ok(()));
+        assert_eq!(RsPib.validate_checksum("RS101134703"), Err(ValidationError::InvalidChecksum));
+
+        assert_eq!(TaxId::new("RS101134702").unwrap().local_value(), "101134702");
+        assert_eq!(TaxId::new("RS101134703").unwrap_err(), ValidationError::InvalidChecksum);
+    }
+}
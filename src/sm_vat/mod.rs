@@ -0,0 +1,82 @@
+use std::collections::HashMap;
+use lazy_static::lazy_static;
+use regex::Regex;
+use crate::TaxIdType;
+use crate::offline::Offline;
+use crate::verification::Verifier;
+
+lazy_static! {
+    #[derive(Debug)]
+    pub static ref SM_VAT_PATTERN: HashMap<String, Regex> = {
+        let mut m = HashMap::new();
+        m.insert("SM".to_string(), Regex::new(r"^SM[0-9]{5}$").unwrap());
+        m
+    };
+}
+
+#[derive(Debug)]
+pub struct SmVat;
+
+impl TaxIdType for SmVat {
+    fn name(&self) -> &'static str {
+        "sm_vat"
+    }
+
+    fn human_name(&self) -> &'static str {
+        "San Marino VAT number (COE)"
+    }
+
+    fn example(&self, tax_country_code: &str) -> Option<&'static str> {
+        (tax_country_code == "SM").then_some("SM24165")
+    }
+
+    fn local_value_lengths(&self, tax_country_code: &str) -> Option<&'static [usize]> {
+        // The Codice Operatore Economico
+        (tax_country_code == "SM").then_some(&[5])
+    }
+
+    fn syntax_map(&self) -> &'static HashMap<String, Regex> {
+        &SM_VAT_PATTERN
+    }
+
+    fn country_code_from_tax_country(&self, tax_country_code: &str) -> String {
+        tax_country_code.to_string()
+    }
+
+    fn verifier(&self) -> &'static dyn Verifier {
+        &Offline
+    }
+
+    fn has_live_verifier(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "sm_vat")]
+    #[test]
+    fn test_sm_vats() {
+        let valid_vat_numbers = vec![
+            "SM24165",
+            "SM00001",
+        ];
+        let invalid_vat_numbers = vec![
+            "SM2416",
+            "SM241650",
+            "SM2416A",
+            "SM 24165",
+            "IT24165",
+        ];
+
+        for valid in valid_vat_numbers {
+            assert!(SmVat::validate_syntax(&SmVat, valid).is_ok());
+        }
+
+        for invalid in invalid_vat_numbers {
+            assert!(SmVat::validate_syntax(&SmVat, invalid).is_err());
+        }
+    }
+}
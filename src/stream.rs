@@ -0,0 +1,110 @@
+use futures_util::stream::{Stream, StreamExt};
+
+use crate::errors::VerificationError;
+use crate::verification::Verification;
+use crate::TaxId;
+
+/// Verifies tax ids as they come in, with at most `concurrency` verifications in flight,
+/// and yields each tax id with its result as soon as that verification completes.
+/// Results are therefore not in input order.
+///
+/// Requires the `async` feature. The blocking verifications run on a thread each, so any
+/// executor works. On wasm targets `TaxId::verify_async` is used instead.
+pub fn verify_stream(
+    ids: impl Stream<Item = TaxId>,
+    concurrency: usize,
+) -> impl Stream<Item = (TaxId, Result<Verification, VerificationError>)> {
+    #[cfg(not(target_arch = "wasm32"))]
+    { verify_stream_with(ids, concurrency, |tax_id| tax_id.verify()) }
+    #[cfg(target_arch = "wasm32")]
+    {
+        ids.map(|tax_id| async move {
+            let result = tax_id.verify_async().await;
+            (tax_id, result)
+        })
+        .buffer_unordered(concurrency.max(1))
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn verify_stream_with(
+    ids: impl Stream<Item = TaxId>,
+    concurrency: usize,
+    verify: fn(&TaxId) -> Result<Verification, VerificationError>,
+) -> impl Stream<Item = (TaxId, Result<Verification, VerificationError>)> {
+    ids.map(move |tax_id| {
+        let (sender, receiver) = futures_channel::oneshot::channel();
+        std::thread::spawn(move || {
+            let result = verify(&tax_id);
+            let _ = sender.send((tax_id, result));
+        });
+        async move { receiver.await.expect("verification thread panicked") }
+    })
+    .buffer_unordered(concurrency.max(1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::future::Future;
+    use std::sync::Arc;
+    use std::task::{Context, Poll, Wake};
+
+    struct ThreadWaker(std::thread::Thread);
+
+    impl Wake for ThreadWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let mut future = std::pin::pin!(future);
+        let waker = Arc::new(ThreadWaker(std::thread::current())).into();
+        let mut context = Context::from_waker(&waker);
+        loop {
+            match future.as_mut().poll(&mut context) {
+                Poll::Ready(output) => return output,
+                Poll::Pending => std::thread::park(),
+            }
+        }
+    }
+
+    fn mock_verify(tax_id: &TaxId) -> Result<Verification, VerificationError> {
+        // Later inputs finish first, so the output order differs from the input order
+        let delay = 30 - tax_id.local_value().len() as u64;
+        std::thread::sleep(std::time::Duration::from_millis(delay));
+        Ok(Verification::new(
+            crate::VerificationStatus::Verified,
+            serde_json::json!({"vatNumber": tax_id.local_value()}),
+        ))
+    }
+
+    #[cfg(feature = "eu_vat")]
+    #[test]
+    fn test_verify_stream_yields_every_input() {
+        let values = ["DE136695976", "SE556703748501", "ATU13585627", "NL004495445B01"];
+        let ids = futures_util::stream::iter(values.map(|value| TaxId::new(value).unwrap()));
+
+        let results: Vec<(TaxId, Result<Verification, VerificationError>)> =
+            block_on(verify_stream_with(ids, 2, mock_verify).collect());
+
+        let mut yielded: Vec<&str> = results.iter().map(|(tax_id, _)| tax_id.value()).collect();
+        yielded.sort();
+        let mut expected = values.to_vec();
+        expected.sort();
+        assert_eq!(yielded, expected);
+
+        for (tax_id, result) in &results {
+            assert_eq!(result.as_ref().unwrap().data()["vatNumber"], tax_id.local_value());
+        }
+    }
+
+    #[cfg(feature = "eu_vat")]
+    #[test]
+    fn test_verify_stream_empty() {
+        let ids = futures_util::stream::iter(Vec::<TaxId>::new());
+        let results: Vec<_> = block_on(verify_stream_with(ids, 0, mock_verify).collect());
+        assert!(results.is_empty());
+    }
+}
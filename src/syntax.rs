@@ -1,6 +1,6 @@
 use std::collections::HashMap;
 use lazy_static::lazy_static;
-use regex::Regex;
+use regex::{Regex, RegexSet};
 
 #[cfg(feature = "ch_vat")]
 use crate::ch_vat::ChVat;
@@ -10,11 +10,19 @@ use crate::eu_vat::EuVat;
 use crate::gb_vat::GbVat;
 #[cfg(feature = "no_vat")]
 use crate::no_vat::NoVat;
+#[cfg(feature = "sm_vat")]
+use crate::sm_vat::SmVat;
+#[cfg(feature = "rs_pib")]
+use crate::rs_pib::RsPib;
+#[cfg(feature = "ua_edrpou")]
+use crate::ua_edrpou::UaEdrpou;
+#[cfg(feature = "ae_trn")]
+use crate::ae_trn::AeTrn;
 use crate::TaxIdType;
 
 lazy_static! {
     #[derive(Debug)]
-    pub static ref SYNTAX: HashMap<String, Regex> = {
+    pub static ref SYNTAX: HashMap<&'static str, &'static Regex> = {
         let mut m = HashMap::new();
 
         let types: Vec<Box<dyn TaxIdType>> = vec![
@@ -24,6 +32,14 @@ lazy_static! {
             Box::new(ChVat),
             #[cfg(feature = "no_vat")]
             Box::new(NoVat),
+            #[cfg(feature = "sm_vat")]
+            Box::new(SmVat),
+            #[cfg(feature = "rs_pib")]
+            Box::new(RsPib),
+            #[cfg(feature = "ua_edrpou")]
+            Box::new(UaEdrpou),
+            #[cfg(feature = "ae_trn")]
+            Box::new(AeTrn),
             #[cfg(feature = "eu_vat")]
             Box::new(EuVat),
         ];
@@ -31,10 +47,145 @@ lazy_static! {
         for t in types {
             let syntax_map = t.syntax_map();
             for (code, pattern) in syntax_map {
-                m.insert(code.to_string(), pattern.clone());
+                m.insert(code.as_str(), pattern);
             }
         }
 
         m
     };
 }
+
+struct CountryDetector {
+    prefixed: RegexSet,
+    unprefixed: RegexSet,
+    tax_country_codes: Vec<&'static str>,
+}
+
+impl CountryDetector {
+    fn single_match(&self, set: &RegexSet, value: &str) -> Option<&'static str> {
+        let mut matches = set.matches(value).into_iter();
+        match (matches.next(), matches.next()) {
+            (Some(i), None) => Some(self.tax_country_codes[i]),
+            _ => None,
+        }
+    }
+}
+
+lazy_static! {
+    static ref DETECTOR: CountryDetector = {
+        let tax_country_codes: Vec<&'static str> = SYNTAX.keys().copied().collect();
+        let patterns: Vec<&str> = tax_country_codes.iter().map(|code| SYNTAX[*code].as_str()).collect();
+        let unprefixed = tax_country_codes.iter().zip(&patterns)
+            .map(|(code, pattern)| pattern.replacen(code, "", 1));
+
+        CountryDetector {
+            prefixed: RegexSet::new(&patterns).unwrap(),
+            unprefixed: RegexSet::new(unprefixed).unwrap(),
+            tax_country_codes,
+        }
+    };
+}
+
+/// Detects which tax country code a value belongs to, with or without its country prefix.
+///
+/// A value matching a country's pattern including its prefix wins. Otherwise the value is
+/// matched as a bare local value, which returns `None` when more than one country accepts it,
+/// e.g. a 9 digit number that is valid syntax in several countries.
+pub fn detect_country(value: &str) -> Option<&'static str> {
+    DETECTOR.single_match(&DETECTOR.prefixed, value)
+        .or_else(|| DETECTOR.single_match(&DETECTOR.unprefixed, value))
+}
+
+/// Returns the tax country codes of all enabled countries, sorted alphabetically.
+pub fn supported_countries() -> Vec<&'static str> {
+    let mut codes: Vec<&'static str> = SYNTAX.keys().copied().collect();
+    codes.sort_unstable();
+    codes
+}
+
+/// Iterates over `(tax_country_code, regex_source)` for every enabled country, sorted by
+/// tax country code. Handy to generate an always up-to-date support table.
+pub fn syntax_patterns() -> impl Iterator<Item = (&'static str, &'static str)> {
+    let mut patterns: Vec<(&'static str, &'static str)> = SYNTAX.iter()
+        .map(|(code, pattern)| (*code, pattern.as_str()))
+        .collect();
+    patterns.sort_unstable_by_key(|(code, _)| *code);
+    patterns.into_iter()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "eu_vat")]
+    #[test]
+    fn test_detect_country_prefixed() {
+        assert_eq!(detect_country("SE556703748501"), Some("SE"));
+        assert_eq!(detect_country("ATU12345678"), Some("AT"));
+        assert_eq!(detect_country("EL123456789"), Some("EL"));
+    }
+
+    #[cfg(feature = "eu_vat")]
+    #[test]
+    fn test_detect_country_unprefixed() {
+        assert_eq!(detect_country("123456789B01"), Some("NL"));
+        assert_eq!(detect_country("12345678901"), None);
+    }
+
+    #[cfg(feature = "eu_vat")]
+    #[test]
+    fn test_detect_country_ambiguous() {
+        // Nine digits is valid syntax for DE, EL, PT, XI and more
+        assert_eq!(detect_country("123456789"), None);
+        // Valid for both AT and ES without a prefix
+        assert_eq!(detect_country("U12345678"), None);
+    }
+
+    #[cfg(feature = "eu_vat")]
+    #[test]
+    fn test_syntax_borrows_type_patterns() {
+        let pattern = SYNTAX.get("SE").unwrap();
+        assert!(std::ptr::eq(*pattern, &EuVat.syntax_map()["SE"]));
+        assert!(pattern.is_match("SE556703748501"));
+    }
+
+    #[test]
+    fn test_syntax_patterns() {
+        let codes: Vec<&str> = syntax_patterns().map(|(code, _)| code).collect();
+        let mut expected: Vec<&str> = SYNTAX.keys().copied().collect();
+        expected.sort();
+        assert_eq!(codes, expected);
+
+        for (code, pattern) in syntax_patterns() {
+            assert_eq!(pattern, SYNTAX[code].as_str());
+        }
+    }
+
+    #[test]
+    fn test_supported_countries() {
+        let codes = supported_countries();
+        assert_eq!(codes.len(), SYNTAX.len());
+        assert!(codes.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    #[cfg(feature = "eu_vat")]
+    #[test]
+    fn test_syntax_patterns_eu_vat() {
+        let pattern = syntax_patterns().find(|(code, _)| *code == "SE").map(|(_, pattern)| pattern);
+        assert_eq!(pattern, Some(r"^SE[0-9]{10}01$"));
+        assert_eq!(syntax_patterns().filter(|(code, _)| *code == "XI").count(), 1);
+    }
+
+    #[test]
+    fn test_detect_country_no_match() {
+        assert_eq!(detect_country("XX123"), None);
+        assert_eq!(detect_country(""), None);
+    }
+
+    #[cfg(feature = "ch_vat")]
+    #[test]
+    fn test_detect_country_ch() {
+        assert_eq!(detect_country("CHE-123.456.789 MWST"), Some("CH"));
+        assert_eq!(detect_country("E-123.456.789"), Some("CH"));
+    }
+}
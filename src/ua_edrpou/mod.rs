@@ -0,0 +1,142 @@
+use std::collections::HashMap;
+use lazy_static::lazy_static;
+use regex::Regex;
+use crate::TaxIdType;
+use crate::checksum::{digits, weighted_sum};
+use crate::errors::ValidationError;
+use crate::offline::Offline;
+use crate::verification::Verifier;
+
+lazy_static! {
+    #[derive(Debug)]
+    pub static ref UA_EDRPOU_PATTERN: HashMap<String, Regex> = {
+        let mut m = HashMap::new();
+        m.insert("UA".to_string(), Regex::new(r"^UA[0-9]{8}$").unwrap());
+        m
+    };
+}
+
+#[derive(Debug)]
+pub struct UaEdrpou;
+
+impl UaEdrpou {
+    /// Mod 11 over the first seven digits with weights 1 to 7, or 7, 1 to 6 for codes from
+    /// 30000000 up to 60000000. When that yields 10, a second pass with every weight raised
+    /// by two is used instead, a remaining 10 becoming 0.
+    fn check_digit(digits: &[u8]) -> u8 {
+        let weights: [u8; 7] = match digits[0] {
+            3..=5 => [7, 1, 2, 3, 4, 5, 6],
+            _ => [1, 2, 3, 4, 5, 6, 7],
+        };
+
+        let mut remainder = weighted_sum(&digits[..7], &weights) % 11;
+        if remainder == 10 {
+            let fallback_weights = weights.map(|w| w + 2);
+            remainder = weighted_sum(&digits[..7], &fallback_weights) % 11;
+        }
+
+        (remainder % 10) as u8
+    }
+}
+
+impl TaxIdType for UaEdrpou {
+    fn name(&self) -> &'static str {
+        "ua_edrpou"
+    }
+
+    fn human_name(&self) -> &'static str {
+        "Ukrainian legal entity code (EDRPOU)"
+    }
+
+    fn example(&self, tax_country_code: &str) -> Option<&'static str> {
+        (tax_country_code == "UA").then_some("UA32855961")
+    }
+
+    fn local_value_lengths(&self, tax_country_code: &str) -> Option<&'static [usize]> {
+        (tax_country_code == "UA").then_some(&[8])
+    }
+
+    fn syntax_map(&self) -> &'static HashMap<String, Regex> {
+        &UA_EDRPOU_PATTERN
+    }
+
+    fn validate_checksum(&self, value: &str) -> Result<(), ValidationError> {
+        let digits = digits(value);
+        if UaEdrpou::check_digit(&digits) != digits[7] {
+            return Err(ValidationError::InvalidChecksum);
+        }
+        Ok(())
+    }
+
+    fn has_checksum(&self, tax_country_code: &str) -> bool {
+        tax_country_code == "UA"
+    }
+
+    fn country_code_from_tax_country(&self, tax_country_code: &str) -> String {
+        tax_country_code.to_string()
+    }
+
+    fn verifier(&self) -> &'static dyn Verifier {
+        &Offline
+    }
+
+    fn has_live_verifier(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TaxId;
+
+    #[cfg(feature = "ua_edrpou")]
+    #[test]
+    fn test_ua_edrpous() {
+        let valid_vat_numbers = vec![
+            "UA32855961",
+            "UA14371467",
+        ];
+        let invalid_vat_numbers = vec![
+            "UA3285596",
+            "UA328559610",
+            "UA3285596A",
+            "UA 32855961",
+        ];
+
+        for valid in valid_vat_numbers {
+            assert!(UaEdrpou::validate_syntax(&UaEdrpou, valid).is_ok());
+        }
+
+        for invalid in invalid_vat_numbers {
+            assert!(UaEdrpou::validate_syntax(&UaEdrpou, invalid).is_err());
+        }
+    }
+
+    #[cfg(feature = "ua_edrpou")]
+    #[test]
+    fn test_validate_checksum_low_range() {
+        assert_eq!(UaEdrpou.validate_checksum("UA14371467"), Ok(()));
+        assert_eq!(UaEdrpou.validate_checksum("UA00031928"), Ok(()));
+        assert_eq!(UaEdrpou.validate_checksum("UA14371468"), Err(ValidationError::InvalidChecksum));
+    }
+
+    #[cfg(feature = "ua_edrpou")]
+    #[test]
+    fn test_validate_checksum_high_range() {
+        assert_eq!(UaEdrpou.validate_checksum("UA32855961"), Ok(()));
+        assert_eq!(UaEdrpou.validate_checksum("UA40135439"), Ok(()));
+        assert_eq!(UaEdrpou.validate_checksum("UA32855962"), Err(ValidationError::InvalidChecksum));
+    }
+
+    #[cfg(feature = "ua_edrpou")]
+    #[test]
+    fn test_validate_checksum_fallback() {
+        assert_eq!(UaEdrpou.validate_checksum("UA10000062"), Ok(()));
+        assert_eq!(UaEdrpou.validate_checksum("UA30000005"), Ok(()));
+        assert_eq!(UaEdrpou.validate_checksum("UA10000060"), Err(ValidationError::InvalidChecksum));
+
+        assert_eq!(TaxId::new("UA32855961").unwrap().local_value(), "32855961");
+        assert_eq!(TaxId::new("UA32855962").unwrap_err(), ValidationError::InvalidChecksum);
+    }
+}
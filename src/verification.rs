@@ -1,4 +1,7 @@
+use std::collections::HashMap;
 use chrono::prelude::*;
+#[cfg(all(not(target_arch = "wasm32"), any(feature = "eu_vat", feature = "gb_vat", feature = "ch_vat", feature = "no_vat")))]
+use lazy_static::lazy_static;
 use crate::errors::VerificationError;
 use crate::TaxId;
 
@@ -6,6 +9,7 @@ use crate::TaxId;
 pub struct VerificationResponse {
     status: u16,
     body: String,
+    retry_after: Option<String>,
 }
 
 impl VerificationResponse {
@@ -13,36 +17,227 @@ impl VerificationResponse {
         VerificationResponse {
             status,
             body,
+            retry_after: None,
         }
     }
 
+    /// Keeps the value of a `Retry-After` response header.
+    #[allow(dead_code)]
+    pub fn with_retry_after(mut self, retry_after: Option<String>) -> VerificationResponse {
+        self.retry_after = retry_after;
+        self
+    }
+
+    #[allow(dead_code)]
     pub fn status(&self) -> u16 { self.status }
     pub fn body(&self) -> &str { &self.body }
+    #[allow(dead_code)]
+    pub fn retry_after(&self) -> Option<&str> { self.retry_after.as_deref() }
 }
 
-#[derive(Debug, PartialEq, Clone, Copy)]
+#[derive(Debug, PartialEq, Clone)]
 pub enum VerificationStatus {
     /// Represents a successful verification where the government database confirmed the ID as legitimate.
+    ///
+    /// Some VIES member states confirm a number without sharing the trader's name and address,
+    /// reported as `---`. Such a verification is still valid, see `Verification::has_trader_details`.
     Verified,
     /// Represents an unsuccessful verification where the government database identified the ID as illegitimate.
+    ///
+    /// When the government database refused the input as malformed instead of looking it up,
+    /// the data holds `"reason": "invalid_input"`.
     Unverified,
     /// Represents a case where verification was not possible due to certain reasons (e.g., government database was unavailable).
     Unavailable(UnavailableReason),
 }
 
-#[derive(Debug, PartialEq, Clone, Copy)]
+impl VerificationStatus {
+    /// Whether the government database gave a final answer, `Verified` or `Unverified`.
+    /// `Unavailable` is provisional and worth retrying.
+    pub fn is_definitive(&self) -> bool {
+        !matches!(self, VerificationStatus::Unavailable(_))
+    }
+
+    /// Whether this status should replace `other` when keeping the best known result,
+    /// i.e. a definitive status is better than an unavailable one. Two definitive or two
+    /// unavailable statuses are equally trusted, so neither is better.
+    pub fn better_than(&self, other: &VerificationStatus) -> bool {
+        self.is_definitive() && !other.is_definitive()
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
 pub enum UnavailableReason {
     ServiceUnavailable,
     Timeout,
     Block,
     RateLimit,
+    /// A fault reported by the government database that this crate doesn't know about (yet).
+    /// Holds the fault code as reported.
+    Other(String),
 }
 
-#[derive(Debug, PartialEq)]
+/// Options that tweak how a verification is performed and what it retains.
+///
+/// Use `VerificationConfig::default()` for the behaviour of `TaxId::verify`.
+#[derive(Debug, Clone)]
+#[cfg_attr(target_arch = "wasm32", derive(Default))]
+pub struct VerificationConfig {
+    /// Keep the raw government response body on the `Verification`,
+    /// available through `Verification::raw_response`. Off by default.
+    pub retain_raw_response: bool,
+    /// Your own UK VAT registration number (VRN), without the GB prefix.
+    /// When set, HMRC lookups return a `consultationNumber` in `Verification::data`,
+    /// which serves as evidence that the check was performed.
+    pub hmrc_requester_vrn: Option<String>,
+    /// Verify XI (Northern Ireland) numbers against HMRC instead of VIES.
+    /// Requires the `gb_vat` feature, VIES is used regardless without it.
+    pub xi_via_hmrc: bool,
+    /// Keep the original Norwegian keys of BrReg responses. The data is then nested as
+    /// `{"raw": ..., "translated": ...}` instead of only holding the translated keys.
+    pub brreg_keep_original_keys: bool,
+    /// Verify CH numbers through the BFS REST/JSON interface instead of SOAP.
+    /// Requires the `ch_vat` feature. Experimental, the REST interface isn't covered by the
+    /// BFS documentation this crate follows, prefer the default SOAP interface.
+    pub ch_via_rest: bool,
+    /// Overrides of the government database endpoints, i.e. to follow a new service version
+    /// before this crate does.
+    pub endpoints: Endpoints,
+    /// The User-Agent header sent with every request. Defaults to `tax-ids/<version>`.
+    pub user_agent: Option<String>,
+    /// An HTTP(S) proxy to send the requests through, i.e. a corporate egress proxy.
+    /// A client is built per verification when set, unless `client` is given as well,
+    /// in which case that client's own proxy settings apply.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub proxy: Option<reqwest::Proxy>,
+    /// The HTTP client used for the requests. When unset, a client shared across all
+    /// verifications is used so connections and TLS sessions are reused.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub client: Option<reqwest::blocking::Client>,
+    /// Ask for gzip or deflate compressed responses and decompress them. On by default,
+    /// turn it off for a provider that mishandles compression. Like `proxy`, a client is
+    /// built per verification when off, unless `client` is given.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub compression: bool,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Default for VerificationConfig {
+    fn default() -> Self {
+        VerificationConfig {
+            retain_raw_response: false,
+            hmrc_requester_vrn: None,
+            xi_via_hmrc: false,
+            brreg_keep_original_keys: false,
+            ch_via_rest: false,
+            endpoints: Endpoints::default(),
+            user_agent: None,
+            proxy: None,
+            client: None,
+            compression: true,
+        }
+    }
+}
+
+/// Full endpoint overrides per government database, `None` keeps the built-in one.
+#[derive(Debug, Clone, Default)]
+pub struct Endpoints {
+    /// The VIES checkVatService URL.
+    pub vies: Option<String>,
+    /// checkVatService URLs per tax country code, i.e. a member state's national service
+    /// while the central VIES one is down. Countries without an entry use `vies`.
+    pub ms_endpoint_overrides: HashMap<String, String>,
+    /// The HMRC lookup base URL, the VAT numbers are appended as path segments.
+    pub hmrc: Option<String>,
+    /// The BFS PublicServices URL, including its version. IE: https://www.uid-wse-a.admin.ch/V5.0/PublicServices.svc
+    pub bfs: Option<String>,
+    /// The BFS uid-wse namespace used in the SOAP envelope and SOAPAction header,
+    /// for service versions that changed it.
+    pub bfs_namespace: Option<String>,
+    /// The BFS REST ValidateVatNumber URL, used with `VerificationConfig::ch_via_rest`.
+    pub bfs_rest: Option<String>,
+    /// The BrReg enheter base URL, the organisation number is appended as a path segment.
+    pub brreg: Option<String>,
+}
+
+#[cfg(any(feature = "eu_vat", feature = "gb_vat", feature = "ch_vat", feature = "no_vat"))]
+const DEFAULT_USER_AGENT: &str = concat!("tax-ids/", env!("CARGO_PKG_VERSION"));
+
+/// The User-Agent header value to send, as configured or the default.
+#[cfg(any(feature = "eu_vat", feature = "gb_vat", feature = "ch_vat", feature = "no_vat"))]
+pub fn user_agent(config: &VerificationConfig) -> &str {
+    config.user_agent.as_deref().unwrap_or(DEFAULT_USER_AGENT)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(any(feature = "eu_vat", feature = "gb_vat", feature = "ch_vat", feature = "no_vat"))]
+lazy_static! {
+    static ref CLIENT: reqwest::blocking::Client = reqwest::blocking::Client::new();
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(any(feature = "eu_vat", feature = "gb_vat", feature = "ch_vat", feature = "no_vat"))]
+fn shared_client() -> &'static reqwest::blocking::Client {
+    &CLIENT
+}
+
+/// The client to perform a verification request with: the configured one, a new one going
+/// through the configured proxy or without compression, or the shared one.
+/// Cloning a client is cheap as it only clones a handle to the connection pool.
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(any(feature = "eu_vat", feature = "gb_vat", feature = "ch_vat", feature = "no_vat"))]
+pub fn blocking_client(config: &VerificationConfig) -> Result<reqwest::blocking::Client, VerificationError> {
+    if let Some(client) = &config.client {
+        return Ok(client.clone());
+    }
+
+    if config.proxy.is_none() && config.compression {
+        return Ok(shared_client().clone());
+    }
+
+    let mut builder = reqwest::blocking::Client::builder();
+    if let Some(proxy) = &config.proxy {
+        builder = builder.proxy(proxy.clone());
+    }
+    if !config.compression {
+        builder = builder.no_gzip().no_deflate();
+    }
+    builder.build().map_err(VerificationError::HttpError)
+}
+
+/// The version of the shape of `Verification::data`.
+///
+/// Bumped whenever the keys of the data change, i.e. a renamed BrReg translation,
+/// so stored verifications can be migrated.
+pub const DATA_SCHEMA_VERSION: u32 = 1;
+
+/// The service that produced a `Verification`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[non_exhaustive]
+pub enum Provider {
+    /// VIES, the VAT Information Exchange System of the European Commission.
+    Vies,
+    /// HM Revenue & Customs, for GB and, when routed there, XI numbers.
+    Hmrc,
+    /// The Swiss Federal Statistical Office, through either its SOAP or REST interface.
+    Bfs,
+    /// The Brønnøysund Register Centre.
+    BrReg,
+    /// No service, the country can't be verified online.
+    Offline,
+    /// Not produced by a verifier, i.e. built by hand.
+    Unknown,
+}
+
+#[derive(Debug, PartialEq, Clone)]
 pub struct Verification {
     performed_at: DateTime<Local>,
+    schema_version: u32,
+    provider: Provider,
     status: VerificationStatus,
     data: serde_json::Value,
+    raw_response: Option<String>,
+    latency: Option<std::time::Duration>,
 }
 
 impl Verification {
@@ -50,8 +245,12 @@ impl Verification {
     pub fn new(status: VerificationStatus, data: serde_json::Value) -> Verification {
         Verification {
             performed_at: Local::now(),
+            schema_version: DATA_SCHEMA_VERSION,
+            provider: Provider::Unknown,
             status,
             data,
+            raw_response: None,
+            latency: None,
         }
     }
 
@@ -61,7 +260,7 @@ impl Verification {
     /// - Enable/process the transaction upon `VerificationStatus::Verified`.
     /// - Block transaction/provide a validation msg upon `VerificationStatus::Unverified`.
     /// - Enable/process the transaction upon `VerificationStatus::Unavailable` but perform a
-    ///     re-verification at a later stage.
+    ///   re-verification at a later stage.
     pub fn status(&self) -> &VerificationStatus { &self.status }
     /// Additional data selected by the crate owner from the government database response.
     /// This data can be used to provide more context about the verification.
@@ -69,26 +268,293 @@ impl Verification {
     ///
     /// Includes error details in case of an unsuccessful verification.
     ///
-    /// Subject to change in future versions.
+    /// Subject to change in future versions, check `data_schema` before reading stored data.
     pub fn data(&self) -> &serde_json::Value { &self.data }
+    /// The `DATA_SCHEMA_VERSION` the data was produced with.
+    pub fn data_schema(&self) -> u32 { self.schema_version }
+
+    /// The service that produced the verification, i.e. to tell a VIES result for an XI
+    /// number from an HMRC one.
+    pub fn provider(&self) -> Provider { self.provider }
+    /// The unmodified response body from the government database.
+    ///
+    /// Only present when the verification was performed with
+    /// `VerificationConfig::retain_raw_response` enabled.
+    pub fn raw_response(&self) -> Option<&str> { self.raw_response.as_deref() }
+    /// How long the request to the government database took, from sending it until the
+    /// response was read. `None` when no request was timed, i.e. on wasm or for a
+    /// verification parsed with `TaxId::parse_verification`.
+    pub fn latency(&self) -> Option<std::time::Duration> { self.latency }
+    /// A human readable reason for an unsuccessful verification, regardless of the provider.
+    ///
+    /// Looks up the provider-specific fault fields in `data` in order of how descriptive they are.
+    /// Returns `None` for a `Verified` status or when no known field is present.
+    pub fn failure_reason(&self) -> Option<String> {
+        if self.status == VerificationStatus::Verified {
+            return None;
+        }
+
+        ["faultstring", "reason", "message", "error", "code"].iter()
+            .find_map(|key| self.data.get(*key).and_then(|v| v.as_str()))
+            .map(|reason| reason.to_string())
+    }
+    /// Whether the data holds the trader's name or address. False when the government database
+    /// withheld them, as some VIES member states do by answering `---`, or never returns them, like BFS.
+    pub fn has_trader_details(&self) -> bool {
+        let data = self.data.get("translated").unwrap_or(&self.data);
+        ["name", "address"].iter().any(|key| match data.get(*key) {
+            Some(serde_json::Value::String(value)) => !matches!(value.trim(), "" | "---"),
+            Some(serde_json::Value::Object(value)) => !value.is_empty(),
+            _ => false,
+        })
+    }
+    /// How long to wait before retrying a rate limited verification, when the government
+    /// database told. Read from the `retryAfter` key (in seconds) of the data.
+    pub fn retry_after(&self) -> Option<std::time::Duration> {
+        self.data.get("retryAfter")
+            .and_then(|v| v.as_u64())
+            .map(std::time::Duration::from_secs)
+    }
+    /// When the government database says it handled the request, in its own time zone.
+    /// Read from the `requestDate` key of the data.
+    ///
+    /// VIES reports a date with an offset but without a time, i.e. `2021-01-01+01:00`,
+    /// which is taken as midnight.
+    pub fn provider_date(&self) -> Option<DateTime<FixedOffset>> {
+        let date = self.data.get("requestDate")?.as_str()?;
+        DateTime::parse_from_rfc3339(date).ok().or_else(|| {
+            let (day, offset) = date.split_at_checked(10)?;
+            DateTime::parse_from_rfc3339(&format!("{}T00:00:00{}", day, offset)).ok()
+        })
+    }
+    #[allow(dead_code)]
+    pub(crate) fn data_mut(&mut self) -> &mut serde_json::Value { &mut self.data }
+    /// When the verification was performed.
+    pub fn performed_at(&self) -> &DateTime<Local> { &self.performed_at }
+
+    /// Whether the verification was performed longer than `duration` ago.
+    /// Useful to decide when to schedule a re-verification.
+    pub fn is_older_than(&self, duration: std::time::Duration) -> bool {
+        self.is_older_than_at(duration, Local::now())
+    }
+
+    /// Falls back to `previous` when this verification is `Unavailable` and `previous` is
+    /// definitive and at most `max_age` older than this one, i.e. to proceed with a checkout
+    /// on a recent `Verified` while the government database is down.
+    ///
+    /// The fallback keeps the status, data and `performed_at` of `previous`, with a `fallback`
+    /// key in the data describing the unavailable attempt. Otherwise `self` is returned as is.
+    pub fn or_previous(self, previous: &Verification, max_age: std::time::Duration) -> Verification {
+        let fallback = !self.status.is_definitive()
+            && previous.status.is_definitive()
+            && !previous.is_older_than_at(max_age, self.performed_at);
+        if !fallback {
+            return self;
+        }
+
+        let mut verification = previous.clone();
+        if let Some(data) = verification.data.as_object_mut() {
+            data.insert("fallback".to_string(), serde_json::json!({
+                "performedAt": self.performed_at.to_rfc3339(),
+                "status": format!("{:?}", self.status),
+            }));
+        }
+        verification
+    }
+
+    /// Combines two verifications of the same business from different sources, i.e. HMRC and
+    /// VIES for an XI number. `Verified` only if both are, `Unverified` if either is, and
+    /// `Unavailable` otherwise, with the reason of the first unavailable one.
+    ///
+    /// The data of each is kept under `first` and `second`, `performed_at` is the older of the
+    /// two and `provider` the one of `self`.
+    pub fn merge(&self, other: &Verification) -> Verification {
+        use VerificationStatus::{Unavailable, Unverified, Verified};
+
+        let status = match (&self.status, &other.status) {
+            (Verified, Verified) => Verified,
+            (Unverified, _) | (_, Unverified) => Unverified,
+            (Unavailable(reason), _) | (_, Unavailable(reason)) => Unavailable(reason.clone()),
+        };
+
+        Verification {
+            performed_at: self.performed_at.min(other.performed_at),
+            schema_version: DATA_SCHEMA_VERSION,
+            provider: self.provider,
+            status,
+            data: serde_json::json!({
+                "first": self.data,
+                "second": other.data,
+            }),
+            raw_response: None,
+            latency: self.latency.max(other.latency),
+        }
+    }
+
+    fn is_older_than_at(&self, duration: std::time::Duration, now: DateTime<Local>) -> bool {
+        match chrono::Duration::from_std(duration) {
+            Ok(duration) => now.signed_duration_since(self.performed_at) > duration,
+            Err(_) => false,
+        }
+    }
 }
 
+#[cfg(target_arch = "wasm32")]
+pub type ResponseFuture<'a> = std::pin::Pin<Box<dyn std::future::Future<Output = Result<VerificationResponse, VerificationError>> + 'a>>;
+
 pub trait Verifier {
-    fn verify(&self, tax_id: &TaxId) -> Result<Verification, VerificationError> {
-        let response = self.make_request(tax_id)?;
-        let verification = self.parse_response(response)?;
+    #[cfg(not(target_arch = "wasm32"))]
+    fn verify(&self, tax_id: &TaxId, config: &VerificationConfig) -> Result<Verification, VerificationError> {
+        #[cfg(feature = "tracing")]
+        let _span = verify_span(tax_id).entered();
+
+        let started = std::time::Instant::now();
+        let response = self.make_request(tax_id, config)?;
+        let latency = started.elapsed();
+
+        let mut verification = into_verification(self, tax_id, response, config)?;
+        verification.latency = Some(latency);
         Ok(verification)
     }
-    fn make_request(&self, tax_id: &TaxId) -> Result<VerificationResponse, VerificationError>;
+    #[cfg(not(target_arch = "wasm32"))]
+    fn make_request(&self, tax_id: &TaxId, config: &VerificationConfig) -> Result<VerificationResponse, VerificationError>;
+
+    /// The wasm counterpart of `make_request`, using the fetch based `reqwest::Client`
+    /// since blocking requests aren't available in the browser.
+    #[cfg(target_arch = "wasm32")]
+    fn make_request_async<'a>(&'a self, tax_id: &'a TaxId, config: &'a VerificationConfig) -> ResponseFuture<'a>;
+
+    /// The service the verifier queries, recorded in every verification it produces.
+    fn provider(&self) -> Provider;
+
+    /// The URL a verification of the tax id is sent to.
+    fn endpoint(&self, tax_id: &TaxId, config: &VerificationConfig) -> String;
 
     fn parse_response(&self, response: VerificationResponse) -> Result<Verification, VerificationError>;
+
+    /// Same as `parse_response` for verifiers whose parsing depends on the `VerificationConfig`.
+    fn parse_response_with_config(&self, response: VerificationResponse, _config: &VerificationConfig) -> Result<Verification, VerificationError> {
+        self.parse_response(response)
+    }
+
+    /// Adjusts a parsed verification with knowledge of the tax id that was requested,
+    /// for providers whose responses may describe another entity than asked for.
+    fn reconcile(&self, _tax_id: &TaxId, verification: Verification) -> Verification {
+        verification
+    }
+}
+
+/// The wasm counterpart of `Verifier::verify`.
+#[cfg(target_arch = "wasm32")]
+pub async fn verify_async(
+    verifier: &dyn Verifier,
+    tax_id: &TaxId,
+    config: &VerificationConfig,
+) -> Result<Verification, VerificationError> {
+    #[cfg(feature = "tracing")]
+    let span = verify_span(tax_id);
+    let request = verifier.make_request_async(tax_id, config);
+    #[cfg(feature = "tracing")]
+    let request = tracing::Instrument::instrument(request, span.clone());
+
+    let response = request.await?;
+    #[cfg(feature = "tracing")]
+    let _entered = span.enter();
+    into_verification(verifier, tax_id, response, config)
+}
+
+#[cfg(feature = "tracing")]
+fn verify_span(tax_id: &TaxId) -> tracing::Span {
+    tracing::info_span!(
+        "verify",
+        tax_id = tax_id.masked(),
+        country = tax_id.country_code(),
+        scheme = tax_id.tax_id_type(),
+        endpoint = tracing::field::Empty,
+    )
+}
+
+pub fn into_verification<V: Verifier + ?Sized>(
+    verifier: &V,
+    tax_id: &TaxId,
+    response: VerificationResponse,
+    config: &VerificationConfig,
+) -> Result<Verification, VerificationError> {
+    #[cfg(feature = "tracing")]
+    let http_status = response.status();
+    let raw_response = config.retain_raw_response.then(|| response.body().to_string());
+    let mut verification = verifier.reconcile(tax_id, verifier.parse_response_with_config(response, config)?);
+    verification.raw_response = raw_response;
+    verification.provider = verifier.provider();
+
+    #[cfg(feature = "tracing")]
+    tracing::info!(http_status, status = ?verification.status(), "verification performed");
+
+    Ok(verification)
+}
+
+/// Verifies a known tax id to tell whether the verifier's service is reachable, regardless of
+/// the number being verified or not. Timeouts, connection failures, server side or rate limit
+/// status codes and `Unavailable` verifications report the service as down, other errors are
+/// returned as is.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn probe(verifier: &dyn Verifier, tax_id: &TaxId, config: &VerificationConfig) -> Result<bool, VerificationError> {
+    match verifier.verify(tax_id, config) {
+        Ok(verification) => Ok(!matches!(verification.status(), VerificationStatus::Unavailable(_))),
+        Err(e) if e.is_retryable() => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+/// Records the endpoint a verifier is about to call on the current `verify` span.
+/// A no-op unless the `tracing` feature is enabled.
+#[cfg(feature = "tracing")]
+#[cfg(any(feature = "eu_vat", feature = "gb_vat", feature = "ch_vat", feature = "no_vat"))]
+pub fn record_endpoint(endpoint: &str) {
+    tracing::Span::current().record("endpoint", endpoint);
 }
 
+#[cfg(not(feature = "tracing"))]
+#[cfg(any(feature = "eu_vat", feature = "gb_vat", feature = "ch_vat", feature = "no_vat"))]
+pub fn record_endpoint(_endpoint: &str) {}
+
 #[cfg(test)]
 mod tests {
     use serde_json::json;
     use super::*;
 
+    #[test]
+    fn test_status_is_definitive() {
+        assert!(VerificationStatus::Verified.is_definitive());
+        assert!(VerificationStatus::Unverified.is_definitive());
+        assert!(!VerificationStatus::Unavailable(UnavailableReason::Timeout).is_definitive());
+        assert!(!VerificationStatus::Unavailable(UnavailableReason::Other("MS_MAX_CONCURRENT_REQ".to_string())).is_definitive());
+    }
+
+    #[test]
+    fn test_status_better_than() {
+        use VerificationStatus::{Unavailable, Unverified, Verified};
+
+        let rate_limit = Unavailable(UnavailableReason::RateLimit);
+        let timeout = Unavailable(UnavailableReason::Timeout);
+        let pairings = [
+            (Verified, Verified, false),
+            (Verified, Unverified, false),
+            (Verified, rate_limit.clone(), true),
+            (Unverified, Verified, false),
+            (Unverified, Unverified, false),
+            (Unverified, rate_limit.clone(), true),
+            (rate_limit.clone(), Verified, false),
+            (rate_limit.clone(), Unverified, false),
+            (rate_limit.clone(), timeout.clone(), false),
+            (timeout, rate_limit.clone(), false),
+        ];
+
+        for (status, other, expected) in pairings {
+            assert_eq!(status.better_than(&other), expected, "{:?} vs {:?}", status, other);
+        }
+    }
+
     #[test]
     fn test_new_verification() {
         let verification = Verification::new(
@@ -97,18 +563,338 @@ mod tests {
         );
         assert_eq!(verification.status(), &VerificationStatus::Verified);
         assert_eq!(verification.performed_at.date_naive(), Local::now().date_naive());
+        assert_eq!(verification.provider(), Provider::Unknown);
+    }
+
+    #[test]
+    fn test_data_schema() {
+        assert_eq!(DATA_SCHEMA_VERSION, 1);
+        let verification = Verification::new(VerificationStatus::Verified, json!({}));
+        assert_eq!(verification.data_schema(), DATA_SCHEMA_VERSION);
+    }
+
+    #[cfg(any(feature = "eu_vat", feature = "gb_vat", feature = "ch_vat", feature = "no_vat"))]
+    #[test]
+    fn test_user_agent() {
+        assert_eq!(user_agent(&VerificationConfig::default()), concat!("tax-ids/", env!("CARGO_PKG_VERSION")));
+
+        let config = VerificationConfig { user_agent: Some("my-shop/1.0".to_string()), ..Default::default() };
+        assert_eq!(user_agent(&config), "my-shop/1.0");
+    }
+
+    #[cfg(any(feature = "eu_vat", feature = "gb_vat", feature = "ch_vat", feature = "no_vat"))]
+    #[test]
+    fn test_shared_client_is_built_once() {
+        let first = shared_client();
+        let config = VerificationConfig::default();
+
+        for _ in 0..10 {
+            let _client = blocking_client(&config).unwrap();
+            assert!(std::ptr::eq(first, shared_client()));
+        }
+    }
+
+    #[test]
+    fn test_is_older_than() {
+        let verification = Verification::new(VerificationStatus::Verified, json!({}));
+        let hour = std::time::Duration::from_secs(3600);
+        let now = verification.performed_at;
+
+        assert!(!verification.is_older_than(hour));
+        assert!(!verification.is_older_than_at(hour, now + chrono::Duration::minutes(59)));
+        assert!(verification.is_older_than_at(hour, now + chrono::Duration::minutes(61)));
+    }
+
+    #[test]
+    fn test_or_previous_fallback() {
+        let mut previous = Verification::new(VerificationStatus::Verified, json!({"name": "Spotify AB"}));
+        previous.performed_at -= chrono::Duration::hours(2);
+        let unavailable = Verification::new(VerificationStatus::Unavailable(UnavailableReason::Timeout), json!({}));
+        let performed_at = unavailable.performed_at;
+
+        let verification = unavailable.or_previous(&previous, std::time::Duration::from_secs(3 * 3600));
+
+        assert_eq!(verification.status(), &VerificationStatus::Verified);
+        assert_eq!(verification.performed_at(), previous.performed_at());
+        assert_eq!(verification.data()["name"], "Spotify AB");
+        assert_eq!(verification.data()["fallback"], json!({
+            "performedAt": performed_at.to_rfc3339(),
+            "status": "Unavailable(Timeout)",
+        }));
+    }
+
+    #[test]
+    fn test_or_previous_too_old() {
+        let mut previous = Verification::new(VerificationStatus::Verified, json!({}));
+        previous.performed_at -= chrono::Duration::hours(2);
+        let unavailable = Verification::new(VerificationStatus::Unavailable(UnavailableReason::Timeout), json!({}));
+
+        let verification = unavailable.clone().or_previous(&previous, std::time::Duration::from_secs(3600));
+        assert_eq!(verification, unavailable);
+    }
+
+    #[test]
+    fn test_or_previous_keeps_definitive_or_unavailable_previous() {
+        let previous = Verification::new(VerificationStatus::Verified, json!({}));
+        let unverified = Verification::new(VerificationStatus::Unverified, json!({}));
+        let hour = std::time::Duration::from_secs(3600);
+        assert_eq!(unverified.clone().or_previous(&previous, hour), unverified);
+
+        let previous = Verification::new(VerificationStatus::Unavailable(UnavailableReason::RateLimit), json!({}));
+        let unavailable = Verification::new(VerificationStatus::Unavailable(UnavailableReason::Timeout), json!({}));
+        assert_eq!(unavailable.clone().or_previous(&previous, hour), unavailable);
+    }
+
+    #[test]
+    fn test_merge_agree_verified() {
+        let hmrc = Verification::new(VerificationStatus::Verified, json!({"name": "Acme Ltd"}));
+        let vies = Verification::new(VerificationStatus::Verified, json!({"valid": "true"}));
+
+        let merged = hmrc.merge(&vies);
+        assert_eq!(merged.status(), &VerificationStatus::Verified);
+        assert_eq!(merged.performed_at(), hmrc.performed_at());
+        assert_eq!(merged.data(), &json!({
+            "first": {"name": "Acme Ltd"},
+            "second": {"valid": "true"},
+        }));
+    }
+
+    #[test]
+    fn test_merge_disagree() {
+        let verified = Verification::new(VerificationStatus::Verified, json!({}));
+        let unverified = Verification::new(VerificationStatus::Unverified, json!({}));
+        let unavailable = Verification::new(VerificationStatus::Unavailable(UnavailableReason::Timeout), json!({}));
+
+        assert_eq!(verified.merge(&unverified).status(), &VerificationStatus::Unverified);
+        assert_eq!(unverified.merge(&verified).status(), &VerificationStatus::Unverified);
+        assert_eq!(unavailable.merge(&unverified).status(), &VerificationStatus::Unverified);
+    }
+
+    #[test]
+    fn test_merge_one_unavailable() {
+        let verified = Verification::new(VerificationStatus::Verified, json!({}));
+        let timeout = Verification::new(VerificationStatus::Unavailable(UnavailableReason::Timeout), json!({}));
+        let rate_limit = Verification::new(VerificationStatus::Unavailable(UnavailableReason::RateLimit), json!({}));
+
+        assert_eq!(verified.merge(&timeout).status(), &VerificationStatus::Unavailable(UnavailableReason::Timeout));
+        assert_eq!(timeout.merge(&verified).status(), &VerificationStatus::Unavailable(UnavailableReason::Timeout));
+        assert_eq!(rate_limit.merge(&timeout).status(), &VerificationStatus::Unavailable(UnavailableReason::RateLimit));
+    }
+
+    #[test]
+    fn test_has_trader_details() {
+        let vies = Verification::new(VerificationStatus::Verified, json!({"valid": "true", "name": "Spotify AB", "address": "---"}));
+        assert!(vies.has_trader_details());
+
+        let hmrc = Verification::new(VerificationStatus::Verified, json!({"name": "", "address": {"line1": "THE VHQ"}}));
+        assert!(hmrc.has_trader_details());
+
+        let brreg = Verification::new(VerificationStatus::Verified, json!({"raw": {}, "translated": {"name": "Test Company AS"}}));
+        assert!(brreg.has_trader_details());
+
+        let bfs = Verification::new(VerificationStatus::Verified, json!({"ValidateVatNumberResult": "true"}));
+        assert!(!bfs.has_trader_details());
+    }
+
+    /// Serves a single gzip encoded response and returns the request it received.
+    #[cfg(feature = "eu_vat")]
+    fn serve_gzip_once(body: &str) -> (String, std::thread::JoinHandle<String>) {
+        use std::io::{Read, Write};
+
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(body.as_bytes()).unwrap();
+        let body = encoder.finish().unwrap();
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = [0; 4096];
+            let read = stream.read(&mut request).unwrap();
+
+            let head = format!(
+                "HTTP/1.1 200 OK\r\nContent-Encoding: gzip\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                body.len()
+            );
+            stream.write_all(head.as_bytes()).unwrap();
+            stream.write_all(&body).unwrap();
+
+            String::from_utf8_lossy(&request[..read]).to_ascii_lowercase()
+        });
+
+        (endpoint, server)
+    }
+
+    #[cfg(feature = "eu_vat")]
+    #[test]
+    fn test_compressed_response() {
+        let body = r#"
+            <soapenv:Envelope xmlns:soapenv="http://schemas.xmlsoap.org/soap/envelope/">
+                <soapenv:Body>
+                    <checkVat xmlns="urn:ec.europa.eu:taxud:vies:services:checkVat:types">
+                        <countryCode>SE</countryCode>
+                        <vatNumber>556703748501</vatNumber>
+                        <requestDate>2021-01-01+01:00</requestDate>
+                        <valid>true</valid>
+                        <name>Spotify AB</name>
+                        <address>Regeringsgatan 19</address>
+                    </checkVat>
+                </soapenv:Body>
+            </soapenv:Envelope>
+        "#;
+        let tax_id = TaxId::new("SE556703748501").unwrap();
+
+        let (endpoint, server) = serve_gzip_once(body);
+        let config = VerificationConfig {
+            endpoints: Endpoints { vies: Some(endpoint), ..Default::default() },
+            ..Default::default()
+        };
+        let verification = tax_id.verify_with_config(&config).unwrap();
+        assert_eq!(verification.status(), &VerificationStatus::Verified);
+        assert_eq!(verification.data()["name"], "Spotify AB");
+
+        let request = server.join().unwrap();
+        assert!(request.contains("accept-encoding: gzip"), "{}", request);
+    }
+
+    #[cfg(feature = "eu_vat")]
+    #[test]
+    fn test_compression_off() {
+        let tax_id = TaxId::new("SE556703748501").unwrap();
+
+        let (endpoint, server) = serve_gzip_once("<Envelope/>");
+        let config = VerificationConfig {
+            endpoints: Endpoints { vies: Some(endpoint), ..Default::default() },
+            compression: false,
+            ..Default::default()
+        };
+        // The body isn't decompressed, so it doesn't parse
+        assert!(tax_id.verify_with_config(&config).is_err());
+
+        let request = server.join().unwrap();
+        assert!(!request.contains("accept-encoding: gzip"), "{}", request);
+        assert!(!request.contains("deflate"), "{}", request);
+    }
+
+    #[test]
+    fn test_failure_reason() {
+        let vies = Verification::new(
+            VerificationStatus::Unavailable(UnavailableReason::RateLimit),
+            json!({"faultcode": "env:Server", "faultstring": "MS_MAX_CONCURRENT_REQ"})
+        );
+        assert_eq!(vies.failure_reason(), Some("MS_MAX_CONCURRENT_REQ".to_string()));
+
+        let hmrc = Verification::new(
+            VerificationStatus::Unverified,
+            json!({"code": "NOT_FOUND", "reason": "targetVrn does not match a registered company"})
+        );
+        assert_eq!(hmrc.failure_reason(), Some("targetVrn does not match a registered company".to_string()));
+
+        let hmrc_code_only = Verification::new(
+            VerificationStatus::Unavailable(UnavailableReason::ServiceUnavailable),
+            json!({"code": "SERVER_ERROR"})
+        );
+        assert_eq!(hmrc_code_only.failure_reason(), Some("SERVER_ERROR".to_string()));
+
+        let bfs = Verification::new(
+            VerificationStatus::Unverified,
+            json!({"faultcode": "s:Client", "faultstring": "Data_validation_failed"})
+        );
+        assert_eq!(bfs.failure_reason(), Some("Data_validation_failed".to_string()));
+
+        let brreg = Verification::new(
+            VerificationStatus::Unavailable(UnavailableReason::ServiceUnavailable),
+            json!({"status": 500, "error": "Internal Server Error", "message": "Internal Server Error"})
+        );
+        assert_eq!(brreg.failure_reason(), Some("Internal Server Error".to_string()));
+
+        let brreg_deleted = Verification::new(
+            VerificationStatus::Unverified,
+            json!({"reason": "deleted", "deletionDate": "2024-03-09"})
+        );
+        assert_eq!(brreg_deleted.failure_reason(), Some("deleted".to_string()));
+    }
+
+    #[test]
+    fn test_retry_after() {
+        let hmrc = Verification::new(
+            VerificationStatus::Unavailable(UnavailableReason::RateLimit),
+            json!({"code": "MESSAGE_THROTTLED_OUT", "retryAfter": 30})
+        );
+        assert_eq!(hmrc.retry_after(), Some(std::time::Duration::from_secs(30)));
+
+        let bfs = Verification::new(
+            VerificationStatus::Unavailable(UnavailableReason::RateLimit),
+            json!({"faultstring": "Request_limit_exceeded", "retryAfter": 60})
+        );
+        assert_eq!(bfs.retry_after(), Some(std::time::Duration::from_secs(60)));
+
+        // VIES doesn't tell when to retry
+        let vies = Verification::new(
+            VerificationStatus::Unavailable(UnavailableReason::RateLimit),
+            json!({"faultcode": "env:Server", "faultstring": "MS_MAX_CONCURRENT_REQ"})
+        );
+        assert_eq!(vies.retry_after(), None);
+
+        let http_date = Verification::new(
+            VerificationStatus::Unavailable(UnavailableReason::RateLimit),
+            json!({"retryAfter": "Wed, 21 Oct 2015 07:28:00 GMT"})
+        );
+        assert_eq!(http_date.retry_after(), None);
+    }
+
+    #[test]
+    fn test_provider_date() {
+        let verification = Verification::new(VerificationStatus::Verified, json!({"requestDate": "2021-01-01+01:00"}));
+        let expected = FixedOffset::east_opt(3600).unwrap().with_ymd_and_hms(2021, 1, 1, 0, 0, 0).unwrap();
+        assert_eq!(verification.provider_date(), Some(expected));
+
+        let verification = Verification::new(VerificationStatus::Verified, json!({"requestDate": "2024-05-06T09:18:58+01:00"}));
+        let expected = FixedOffset::east_opt(3600).unwrap().with_ymd_and_hms(2024, 5, 6, 9, 18, 58).unwrap();
+        assert_eq!(verification.provider_date(), Some(expected));
+    }
+
+    #[test]
+    fn test_provider_date_missing_or_invalid() {
+        let verification = Verification::new(VerificationStatus::Unverified, json!({"reason": "not_found"}));
+        assert_eq!(verification.provider_date(), None);
+
+        let verification = Verification::new(VerificationStatus::Verified, json!({"requestDate": "01/01/2021"}));
+        assert_eq!(verification.provider_date(), None);
+
+        let verification = Verification::new(VerificationStatus::Verified, json!({"requestDate": "2021"}));
+        assert_eq!(verification.provider_date(), None);
+    }
+
+    #[test]
+    fn test_failure_reason_none() {
+        let verified = Verification::new(VerificationStatus::Verified, json!({"name": "Test Company AS"}));
+        assert_eq!(verified.failure_reason(), None);
+
+        let unverified = Verification::new(VerificationStatus::Unverified, json!({"valid": "false"}));
+        assert_eq!(unverified.failure_reason(), None);
     }
 
     struct TestVerifier;
 
     impl Verifier for TestVerifier {
-        fn make_request(&self, _tax_id: &TaxId) -> Result<VerificationResponse, VerificationError> {
+        fn make_request(&self, _tax_id: &TaxId, _config: &VerificationConfig) -> Result<VerificationResponse, VerificationError> {
+            #[cfg(any(feature = "eu_vat", feature = "gb_vat", feature = "ch_vat", feature = "no_vat"))]
+            record_endpoint("https://example.com/verify");
             Ok(VerificationResponse::new(
                 200,
                 "test".to_string()
             ))
         }
 
+        fn provider(&self) -> Provider {
+            Provider::Unknown
+        }
+
+        fn endpoint(&self, _tax_id: &TaxId, _config: &VerificationConfig) -> String {
+            "https://example.com/verify".to_string()
+        }
+
         fn parse_response(&self, response: VerificationResponse) -> Result<Verification, VerificationError> {
             let data = json!({
                 "key": "value"
@@ -126,10 +912,108 @@ mod tests {
     fn test_verify_for(value: &str) {
         let tax_id = TaxId::new(value).unwrap();
         let verifier = TestVerifier;
-        let verification = verifier.verify(&tax_id).unwrap();
+        let verification = verifier.verify(&tax_id, &VerificationConfig::default()).unwrap();
         assert_eq!(verification.status(), &VerificationStatus::Verified);
         assert_eq!(verification.performed_at.date_naive(), Local::now().date_naive());
         assert_eq!(verification.data().get("key").unwrap(), "value");
+        assert_eq!(verification.raw_response(), None);
+        assert!(verification.latency().is_some());
+    }
+
+    #[cfg(feature="eu_vat")]
+    #[test]
+    fn test_verify_records_latency() {
+        struct SlowVerifier;
+
+        impl Verifier for SlowVerifier {
+            fn make_request(&self, tax_id: &TaxId, config: &VerificationConfig) -> Result<VerificationResponse, VerificationError> {
+                std::thread::sleep(std::time::Duration::from_millis(50));
+                TestVerifier.make_request(tax_id, config)
+            }
+
+            fn provider(&self) -> Provider {
+                Provider::Unknown
+            }
+
+            fn endpoint(&self, tax_id: &TaxId, config: &VerificationConfig) -> String {
+                TestVerifier.endpoint(tax_id, config)
+            }
+
+            fn parse_response(&self, response: VerificationResponse) -> Result<Verification, VerificationError> {
+                TestVerifier.parse_response(response)
+            }
+        }
+
+        let tax_id = TaxId::new("SE123456789101").unwrap();
+        let verification = SlowVerifier.verify(&tax_id, &VerificationConfig::default()).unwrap();
+        let latency = verification.latency().unwrap();
+        assert!(latency >= std::time::Duration::from_millis(50), "{:?}", latency);
+        assert!(latency < std::time::Duration::from_secs(5), "{:?}", latency);
+
+        assert_eq!(Verification::new(VerificationStatus::Verified, json!({})).latency(), None);
+    }
+
+    #[cfg(feature="eu_vat")]
+    #[test]
+    fn test_probe() {
+        struct MockVerifier(u16, &'static str);
+
+        impl Verifier for MockVerifier {
+            fn make_request(&self, _tax_id: &TaxId, _config: &VerificationConfig) -> Result<VerificationResponse, VerificationError> {
+                Ok(VerificationResponse::new(self.0, self.1.to_string()))
+            }
+
+            fn provider(&self) -> Provider {
+                Provider::Unknown
+            }
+
+            fn endpoint(&self, _tax_id: &TaxId, _config: &VerificationConfig) -> String {
+                "https://example.com/verify".to_string()
+            }
+
+            fn parse_response(&self, response: VerificationResponse) -> Result<Verification, VerificationError> {
+                let status = match (response.status(), response.body()) {
+                    (200, "valid") => VerificationStatus::Verified,
+                    (200, "invalid") => VerificationStatus::Unverified,
+                    (200, "busy") => VerificationStatus::Unavailable(UnavailableReason::ServiceUnavailable),
+                    (200, body) => return Err(VerificationError::UnexpectedResponse(body.to_string())),
+                    (status, _) => return Err(VerificationError::UnexpectedStatusCode(status)),
+                };
+                Ok(Verification::new(status, json!({})))
+            }
+        }
+
+        let tax_id = TaxId::new("SE123456789101").unwrap();
+        let config = VerificationConfig::default();
+
+        // Up, whether or not the probed number is verified
+        assert!(probe(&MockVerifier(200, "valid"), &tax_id, &config).unwrap());
+        assert!(probe(&MockVerifier(200, "invalid"), &tax_id, &config).unwrap());
+
+        // Down
+        assert!(!probe(&MockVerifier(200, "busy"), &tax_id, &config).unwrap());
+        assert!(!probe(&MockVerifier(503, ""), &tax_id, &config).unwrap());
+        assert!(!probe(&MockVerifier(429, ""), &tax_id, &config).unwrap());
+
+        // Neither, the response can't be made sense of
+        assert!(matches!(
+            probe(&MockVerifier(200, "<html>"), &tax_id, &config),
+            Err(VerificationError::UnexpectedResponse(_))
+        ));
+        assert!(matches!(
+            probe(&MockVerifier(403, ""), &tax_id, &config),
+            Err(VerificationError::UnexpectedStatusCode(403))
+        ));
+    }
+
+    #[cfg(feature="eu_vat")]
+    #[test]
+    fn test_verify_retains_raw_response() {
+        let tax_id = TaxId::new("SE123456789101").unwrap();
+        let config = VerificationConfig { retain_raw_response: true, ..Default::default() };
+        let verification = TestVerifier.verify(&tax_id, &config).unwrap();
+        assert_eq!(verification.status(), &VerificationStatus::Verified);
+        assert_eq!(verification.raw_response(), Some("test"));
     }
 
 
@@ -156,4 +1040,65 @@ mod tests {
     fn test_verify_for_no() {
         test_verify_for("NO123456789");
     }
-}
\ No newline at end of file
+
+    #[cfg(feature="sm_vat")]
+    #[test]
+    fn test_verify_for_sm() {
+        test_verify_for("SM24165");
+    }
+
+    #[cfg(feature="rs_pib")]
+    #[test]
+    fn test_verify_for_rs() {
+        test_verify_for("RS101134702");
+    }
+
+    #[cfg(feature="ua_edrpou")]
+    #[test]
+    fn test_verify_for_ua() {
+        test_verify_for("UA32855961");
+    }
+
+    #[cfg(feature="ae_trn")]
+    #[test]
+    fn test_verify_for_ae() {
+        test_verify_for("AE100235617900003");
+    }
+
+    #[cfg(all(feature = "tracing", feature = "eu_vat"))]
+    #[test]
+    fn test_verify_emits_tracing_events() {
+        use std::io::Write;
+        use std::sync::{Arc, Mutex};
+
+        #[derive(Clone, Default)]
+        struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+        impl Write for Buffer {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.0.lock().unwrap().write(buf)
+            }
+            fn flush(&mut self) -> std::io::Result<()> { Ok(()) }
+        }
+
+        let buffer = Buffer::default();
+        let writer = buffer.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_writer(move || writer.clone())
+            .with_ansi(false)
+            .finish();
+
+        tracing::subscriber::with_default(subscriber, || {
+            test_verify_for("SE123456789101");
+        });
+
+        let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        assert!(output.contains("tax_id=\"SE**********01\""), "{}", output);
+        assert!(!output.contains("SE123456789101"), "{}", output);
+        assert!(output.contains("country=\"SE\""), "{}", output);
+        assert!(output.contains("scheme=\"eu_vat\""), "{}", output);
+        assert!(output.contains("endpoint=\"https://example.com/verify\""), "{}", output);
+        assert!(output.contains("http_status=200"), "{}", output);
+        assert!(output.contains("status=Verified"), "{}", output);
+    }
+}
@@ -0,0 +1,135 @@
+//! Exercises the public API the way a dependent crate would, per feature.
+//! Run with a single feature to check that feature-off builds keep compiling, i.e.
+//! `cargo test --no-default-features --features gb_vat`.
+
+use tax_ids::{supported_countries, TaxId, ValidationError};
+
+#[test]
+fn test_supported_countries_match_features() {
+    let countries = supported_countries();
+
+    assert_eq!(countries.contains(&"SE"), cfg!(feature = "eu_vat"));
+    assert_eq!(countries.contains(&"XI"), cfg!(feature = "eu_vat"));
+    assert_eq!(countries.contains(&"GB"), cfg!(feature = "gb_vat"));
+    assert_eq!(countries.contains(&"CH"), cfg!(feature = "ch_vat"));
+    assert_eq!(countries.contains(&"NO"), cfg!(feature = "no_vat"));
+    assert_eq!(countries.contains(&"SM"), cfg!(feature = "sm_vat"));
+    assert_eq!(countries.contains(&"RS"), cfg!(feature = "rs_pib"));
+    assert_eq!(countries.contains(&"UA"), cfg!(feature = "ua_edrpou"));
+    assert_eq!(countries.contains(&"AE"), cfg!(feature = "ae_trn"));
+}
+
+#[test]
+fn test_disabled_countries_are_unsupported() {
+    if !cfg!(feature = "gb_vat") {
+        assert_eq!(
+            TaxId::new("GB591819014").unwrap_err(),
+            ValidationError::UnsupportedCountryCode("GB".to_string())
+        );
+    }
+    if !cfg!(feature = "eu_vat") {
+        assert_eq!(
+            TaxId::validate_syntax("SE556703748501"),
+            Err(ValidationError::UnsupportedCountryCode("SE".to_string()))
+        );
+    }
+}
+
+#[cfg(feature = "eu_vat")]
+#[test]
+fn test_eu_vat() {
+    assert!(TaxId::validate_syntax("SE556703748501").is_ok());
+    assert_eq!(TaxId::validate_syntax("SE55670374850"), Err(ValidationError::InvalidSyntax));
+
+    let tax_id = TaxId::new("SE556703748501").unwrap();
+    assert_eq!(tax_id.tax_id_type(), "eu_vat");
+    assert_eq!(tax_id.local_value(), "556703748501");
+    assert!(tax_id.is_eu());
+}
+
+#[cfg(feature = "gb_vat")]
+#[test]
+fn test_gb_vat() {
+    assert!(TaxId::validate_syntax("GB591819014").is_ok());
+    assert_eq!(TaxId::validate_syntax("GB59181901"), Err(ValidationError::InvalidSyntax));
+
+    let tax_id = TaxId::new("GB591819014").unwrap();
+    assert_eq!(tax_id.tax_id_type(), "gb_vat");
+    assert_eq!(tax_id.local_value(), "591819014");
+    assert!(!tax_id.is_eu());
+}
+
+#[cfg(feature = "ch_vat")]
+#[test]
+fn test_ch_vat() {
+    assert!(TaxId::validate_syntax("CHE-116.281.710").is_ok());
+    assert_eq!(TaxId::validate_syntax("CHE-116.281.71"), Err(ValidationError::InvalidSyntax));
+
+    let tax_id = TaxId::new("CHE-116.281.710").unwrap();
+    assert_eq!(tax_id.tax_id_type(), "ch_vat");
+    assert_eq!(tax_id.local_value(), "E-116.281.710");
+    assert!(!tax_id.is_eu());
+}
+
+#[cfg(feature = "no_vat")]
+#[test]
+fn test_no_vat() {
+    assert!(TaxId::validate_syntax("NO974760673MVA").is_ok());
+    assert_eq!(TaxId::validate_syntax("NO97476067MVA"), Err(ValidationError::InvalidSyntax));
+
+    let tax_id = TaxId::new("NO974760673MVA").unwrap();
+    assert_eq!(tax_id.tax_id_type(), "no_vat");
+    assert_eq!(tax_id.local_value(), "974760673MVA");
+    assert!(!tax_id.is_eu());
+}
+
+#[cfg(feature = "sm_vat")]
+#[test]
+fn test_sm_vat() {
+    assert!(TaxId::validate_syntax("SM24165").is_ok());
+    assert_eq!(TaxId::validate_syntax("SM2416"), Err(ValidationError::InvalidSyntax));
+
+    let tax_id = TaxId::new("SM24165").unwrap();
+    assert_eq!(tax_id.tax_id_type(), "sm_vat");
+    assert_eq!(tax_id.local_value(), "24165");
+    assert!(!tax_id.is_eu());
+}
+
+#[cfg(feature = "rs_pib")]
+#[test]
+fn test_rs_pib() {
+    assert!(TaxId::validate_syntax("RS101134702").is_ok());
+    assert_eq!(TaxId::validate_syntax("RS10113470"), Err(ValidationError::InvalidSyntax));
+    assert_eq!(TaxId::new("RS101134703").unwrap_err(), ValidationError::InvalidChecksum);
+
+    let tax_id = TaxId::new("RS101134702").unwrap();
+    assert_eq!(tax_id.tax_id_type(), "rs_pib");
+    assert_eq!(tax_id.local_value(), "101134702");
+    assert!(!tax_id.is_eu());
+}
+
+#[cfg(feature = "ua_edrpou")]
+#[test]
+fn test_ua_edrpou() {
+    assert!(TaxId::validate_syntax("UA32855961").is_ok());
+    assert_eq!(TaxId::validate_syntax("UA3285596"), Err(ValidationError::InvalidSyntax));
+    assert_eq!(TaxId::new("UA32855962").unwrap_err(), ValidationError::InvalidChecksum);
+
+    let tax_id = TaxId::new("UA32855961").unwrap();
+    assert_eq!(tax_id.tax_id_type(), "ua_edrpou");
+    assert_eq!(tax_id.local_value(), "32855961");
+    assert!(!tax_id.is_eu());
+}
+
+#[cfg(feature = "ae_trn")]
+#[test]
+fn test_ae_trn() {
+    assert!(TaxId::validate_syntax("AE100235617900003").is_ok());
+    assert_eq!(TaxId::validate_syntax("AE200235617900003"), Err(ValidationError::InvalidSyntax));
+    assert_eq!(TaxId::new("AE10023561790000").unwrap_err(), ValidationError::InvalidSyntax);
+
+    let tax_id = TaxId::new("AE100235617900003").unwrap();
+    assert_eq!(tax_id.tax_id_type(), "ae_trn");
+    assert_eq!(tax_id.local_value(), "100235617900003");
+    assert!(!tax_id.is_eu());
+}